@@ -42,12 +42,16 @@ fn main() {
 
     dir.sort_unstable_by_key(|entry| entry.file_name());
 
+    // MOD: the exact algorithm can be compared, e.g. `ilp` against `mtz`
+    let algorithm = env::args().nth(4).unwrap_or_else(|| "ilp".to_owned());
+
     rayon::scope_fifo(|scope| {
         for entry in dir {
+            let algorithm = algorithm.clone();
             scope.spawn_fifo(move |_| {
                 // MOD: use real time instead of CPU time, may change in the future
                 let time = std::time::Instant::now();
-                let report = match solve_one(&entry.path(), time_limit) {
+                let report = match solve_one(&entry.path(), time_limit, &algorithm) {
                     Ok(k) => format!("{},{:.3}", k, time.elapsed().as_secs_f32()),
                     Err(_) => format!(" ERROR {:.3}", time.elapsed().as_secs_f32(),),
                 };
@@ -60,10 +64,10 @@ fn main() {
     });
 }
 
-fn solve_one(file_path: &Path, time_limit: Duration) -> Result<usize, String> {
+fn solve_one(file_path: &Path, time_limit: Duration, algorithm: &str) -> Result<usize, String> {
     // Rayon has difficulties cleaning up child processes, so we need a small hack
     // We assume we equire about 100s to reach vc_solver in the execution path
-    let args = ["-n".to_owned(), "-a=ilp".to_owned()];
+    let args = ["-n".to_owned(), format!("-a={}", algorithm)];
     Ok(String::from_utf8(
         Command::cargo_bin("hex")
             .unwrap()
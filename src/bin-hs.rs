@@ -0,0 +1,22 @@
+//! Exact solver for hitting set instances. An instance in the format of PACE
+//! 2025 is read from stdin, and the minimum hitting set is written to stdout.
+
+#![allow(dead_code)]
+mod exact;
+mod graph;
+mod heur;
+mod io;
+mod lower;
+mod util;
+
+fn main() {
+    let (elements, sets) = match io::read_hitting_set() {
+        Ok(instance) => instance,
+        Err(err) => {
+            eprintln!("cannot read the instance: {}", err);
+            std::process::exit(1);
+        }
+    };
+    let solution = exact::hs_solver::solve_hitting_set(sets, elements);
+    io::write_hitting_set(solution);
+}
@@ -10,6 +10,7 @@ use graph::SplitReduce;
 
 fn main() {
     let graph = io::read().unwrap();
-    let (gd, gb, _) = graph.split_reduce();
+    let vertices = graph.total_vertices();
+    let (gd, gb, _, _) = graph.split_reduce(vertices).unwrap();
     println!("{}", gd + gb);
 }
@@ -1,129 +1,1095 @@
 use crate::{
-    graph::{EdgeCycleCover, Graph, SplitReduce},
+    exact::{
+        branching::{self, Branch, BranchingRule},
+        checkpoint::{invalid, Checkpointer, Reader, Writer},
+        transposition::{Entry, Key, TranspositionTable},
+    },
+    graph::{EdgeCycleCover, Folds, Graph, SplitReduce},
     heur::{Heuristic, SimulatedAnnealing},
-    io::{Algorithm, Config},
-    lower,
+    io::{Algorithm, Config, Strategy},
+    lower::{self, Relaxation},
+    util::{Heap, KeyValue, MinItem},
+};
+use rayon::{prelude::*, ThreadPoolBuilder};
+use rustc_hash::FxHashMap;
+use std::{
+    cell::{Cell, RefCell},
+    io::Result,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
 };
 
-fn branch_and_reduce(
+/// Extends a solution of the reduced graph with the vertices forced by the
+/// split reduction, and lifts it through the folds of the undirected part.
+fn lift(mut solution: Vec<u32>, forced: &[u32], folds: &Folds) -> Vec<u32> {
+    solution.extend_from_slice(forced);
+    folds.lift(solution)
+}
+
+thread_local! {
+    /// Every worker keeps its own LP relaxation, which is reused across the
+    /// nodes the worker solves.
+    static RELAXATION: RefCell<Option<Relaxation>> = const { RefCell::new(None) };
+}
+
+/// Returns the LP bound of the node, in a cleared relaxation if `fresh`. The
+/// values of a warm started relaxation depend on the nodes solved before.
+fn lower_bound(gd: &Graph, gb: &Graph, fresh: bool) -> (usize, Vec<u32>, Vec<f64>) {
+    RELAXATION.with(|relaxation| {
+        let mut relaxation = relaxation.borrow_mut();
+        let vertices = gd.total_vertices();
+        if relaxation.as_ref().is_none_or(|r| r.vertices() != vertices) {
+            *relaxation = Some(Relaxation::new(vertices));
+        } else if fresh {
+            relaxation.as_mut().unwrap().clear();
+        }
+        relaxation.as_mut().unwrap().lower_bound(gd, gb)
+    })
+}
+
+/// State shared by all nodes of a branch-and-reduce search.
+#[derive(Clone, Copy)]
+struct Search<'a> {
+    /// Frequency of the split reduction, a frequency of 0 disables it.
+    freq: usize,
+
+    rule: &'a dyn BranchingRule,
+
+    /// The level of the root of the search, which for the search of a
+    /// component is the level of the node that was split into components.
+    root_level: usize,
+
+    /// The budget of the root of the search.
+    root_budget: usize,
+
+    /// The size of the smallest solution found by any worker.
+    incumbent: &'a AtomicUsize,
+
+    /// The children of nodes above this level are solved in parallel.
+    parallel_depth: usize,
+
+    /// Results of residual graphs, if enabled. A sequential search shares one
+    /// table, while every sequential subtree of a parallel search has its own.
+    table: Option<&'a Mutex<TranspositionTable>>,
+
+    /// Memory limit in bytes of the table of every sequential subtree of a
+    /// parallel search, a limit of 0 disables these tables.
+    table_limit: usize,
+
+    /// Number of budgets cut by the incumbent in the search sharing `table`.
+    /// Results that depend on such a cut are not stored.
+    cuts: Option<&'a AtomicUsize>,
+}
+
+impl<'a> Search<'a> {
+    /// Returns whether every node is bounded in a cleared relaxation and
+    /// reduced within the budget of the root less the vertices taken since.
+    /// What the incumbent cuts in a parallel search depends on the scheduling
+    /// of the workers, and with it the nodes a worker bounded before and the
+    /// budgets of the nodes.
+    fn fresh(&self) -> bool {
+        self.parallel_depth > 0
+    }
+
+    /// Caps the budget `k` of a node after `taken` solution vertices to the
+    /// solutions no larger than the incumbent, or returns `None` if there are
+    /// none. Solutions of the same size as the incumbent are kept, so the
+    /// solution that is returned, the smallest one and the one of the first
+    /// branch among those of the same size, is never cut.
+    fn cap(&self, taken: usize, k: usize) -> Option<usize> {
+        let incumbent = self.incumbent.load(Ordering::Relaxed);
+        let capped = incumbent.checked_sub(taken).map(|budget| budget.min(k));
+        if capped != Some(k) {
+            if let Some(cuts) = self.cuts {
+                cuts.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        capped
+    }
+
+    /// Returns the number of budgets cut so far in the search sharing `table`.
+    fn cuts(&self) -> usize {
+        self.cuts.map_or(0, |cuts| cuts.load(Ordering::Relaxed))
+    }
+
+    /// Returns the children of a node at `level`, where the rule may branch
+    /// differently at the root.
+    fn branch(&self, level: usize, gd: &Graph, gb: &Graph, values: &[f64]) -> Vec<Branch> {
+        if level == self.root_level {
+            self.rule.branch_root(gd, gb, values)
+        } else {
+            self.rule.branch(gd, gb, values)
+        }
+    }
+
+    fn publish(&self, size: usize) {
+        self.incumbent.fetch_min(size, Ordering::Relaxed);
+    }
+
+    fn lookup(&self, key: Option<Key>) -> Option<Entry> {
+        self.table?.lock().unwrap().get(&key?)
+    }
+
+    fn store(&self, key: Option<Key>, entry: Entry) {
+        if let (Some(table), Some(key)) = (self.table, key) {
+            table.lock().unwrap().insert(key, entry);
+        }
+    }
+
+    /// Stores an optimal solution of the residual graph of `key` found by
+    /// branching. The solution a fresh search finds depends on the budgets of
+    /// the path to the node, so only its size is stored.
+    fn store_optimal(&self, key: Option<Key>, solution: &[u32]) {
+        if self.fresh() {
+            self.store(key, Entry::LowerBound(solution.len()));
+        } else {
+            self.store(key, Entry::Optimal(solution.to_vec()));
+        }
+    }
+}
+
+/// The position of a node in the search.
+#[derive(Clone, Copy)]
+struct Position {
+    /// Number of nodes since the last split reduction.
+    depth: usize,
+
+    /// Number of branchings since the root.
+    level: usize,
+
+    /// Number of solution vertices fixed by the ancestors.
+    taken: usize,
+}
+
+impl Position {
+    fn root() -> Position {
+        Position {
+            depth: 0,
+            level: 0,
+            taken: 0,
+        }
+    }
+}
+
+/// A node after its reductions and lower bound, which still has to be
+/// branched on.
+struct Bounded {
+    gd: Graph,
+    gb: Graph,
+    forced: Vec<u32>,
+    folds: Folds,
+    position: Position,
+    key: Option<Key>,
+
+    /// The budget of the node, below the size of the candidate if it fits.
+    k: usize,
+    lower_bound: usize,
+
+    /// The heuristic solution of the LP relaxation, if it fits the budget.
+    candidate: Option<Vec<u32>>,
+
+    /// The values of the vertices in the optimal LP relaxation.
+    values: Vec<f64>,
+}
+
+enum Evaluation {
+    /// The node has no solution within its budget.
+    Pruned,
+
+    /// An optimal solution of the node.
+    Solved(Vec<u32>),
+
+    Open(Box<Bounded>),
+}
+
+/// Applies the reductions to `graph` and bounds it, which settles the node
+/// unless it has to be branched on. `stack` is the stack of the sequential
+/// search the node belongs to, if it writes checkpoints.
+fn evaluate(
     graph: Graph,
     upper_bound: usize,
-    freq: usize,
-    mut depth: usize,
-) -> Option<Vec<u32>> {
+    mut position: Position,
+    search: &Search,
+    stack: Option<Stack>,
+) -> Evaluation {
     if !graph.is_cyclic() {
-        return Some(vec![]);
+        search.publish(position.taken);
+        return Evaluation::Solved(vec![]);
     }
 
     let mut k = upper_bound;
-    let mut best_solution = None;
+    let budget = if search.fresh() {
+        match search.root_budget.checked_sub(position.taken) {
+            Some(budget) => budget,
+            None => return Evaluation::Pruned,
+        }
+    } else {
+        k
+    };
 
     // let (gd, gb, mut forced) = graph.split_reduce();
-    let (gd, gb, mut forced) = if depth % freq == 0 {
-        depth = 1;
-        graph.split_reduce()
+    // a frequency of 0 disables the split reduction
+    let split = if search.freq != 0 && position.depth.is_multiple_of(search.freq) {
+        position.depth = 1;
+        // the split reduction fails if more than `budget` vertices are forced
+        graph.split_reduce(budget)
     } else {
-        depth += 1;
+        position.depth += 1;
         let (gd, gb) = graph.split();
-        (gd, gb, vec![])
+        Some((gd, gb, vec![], Folds::default()))
+    };
+    let (gd, gb, forced, folds) = match split {
+        Some(split) => split,
+        None => return Evaluation::Pruned,
     };
 
-    if forced.len() > k {
-        return None;
+    // every fold accounts for exactly one vertex of the solution
+    if forced.len() + folds.len() > k {
+        return Evaluation::Pruned;
     }
 
-    k -= forced.len();
+    k -= forced.len() + folds.len();
+    let budget = budget - forced.len() - folds.len();
+    position.taken += forced.len() + folds.len();
+
+    // the residual graph may already have been solved or bounded elsewhere
+    let key = search.table.map(|_| Key::new(&gd, &gb));
+    let mut known_bound = 0;
+    match search.lookup(key) {
+        Some(Entry::Optimal(solution)) => {
+            if solution.len() > k {
+                return Evaluation::Pruned;
+            }
+            search.publish(position.taken + solution.len());
+            return Evaluation::Solved(lift(solution, &forced, &folds));
+        }
+        Some(Entry::LowerBound(bound)) if bound > k => return Evaluation::Pruned,
+        Some(Entry::LowerBound(bound)) => known_bound = bound,
+        None => {}
+    }
 
-    let (lower_bound, mut candidate) = lower::lower_bound(&gd, &gb);
+    // Branching may have split the graph into independent strongly connected
+    // components, which are solved separately with their own bounds.
+    let components = cyclic_components(&(gd.clone() + gb.clone()));
+    if components.len() > 1 {
+        // the solutions of the components only combine once all of them are
+        // solved, so a checkpoint within their searches holds this node
+        let whole = stack.map(|stack| Frame {
+            gd: &gd,
+            gb: &gb,
+            forced: &forced,
+            folds: &folds,
+            position,
+            lower_bound: 0,
+            branches: &[],
+            next: Cell::new(0),
+            whole: true,
+            parent: stack.top,
+        });
+        let stack = stack.zip(whole.as_ref()).map(|(stack, whole)| Stack {
+            top: Some(whole),
+            ..stack
+        });
+        let before = search.cuts();
+        let result = solve_components(components, budget, position, search, stack);
+        let exact = search.cuts() == before;
+        return match result.filter(|solution| solution.len() <= k) {
+            Some(solution) => {
+                if exact {
+                    search.store_optimal(key, &solution);
+                }
+                search.publish(position.taken + solution.len());
+                Evaluation::Solved(lift(solution, &forced, &folds))
+            }
+            None => {
+                if exact {
+                    search.store(key, Entry::LowerBound(k + 1));
+                }
+                Evaluation::Pruned
+            }
+        };
+    }
+
+    let (lower_bound, candidate, values) = lower_bound(&gd, &gb, search.fresh());
+    let lower_bound = lower_bound.max(known_bound);
+    // other workers may have found a smaller solution in the meantime
+    k = match search.cap(position.taken, k) {
+        Some(k) => k,
+        None => return Evaluation::Pruned,
+    };
     if lower_bound > k {
-        return None;
+        search.store(key, Entry::LowerBound(lower_bound));
+        return Evaluation::Pruned;
     }
 
+    let mut best_solution = None;
     if candidate.len() <= k && gd.is_acyclic_with_fvs(&candidate) && gb.is_acyclic_with_fvs(&candidate) {
+        search.publish(position.taken + candidate.len());
         if candidate.len() == lower_bound {
-            candidate.append(&mut forced);
-            return Some(candidate);
+            search.store(key, Entry::Optimal(candidate.clone()));
+            return Evaluation::Solved(lift(candidate, &forced, &folds));
         }
         k = candidate.len() - 1;
         best_solution = Some(candidate);
     }
 
-    let mut stars = gb.stars();
-    if stars.is_empty() {
-        let mut cycles = gd.edge_cycle_cover();
-        cycles.sort_unstable_by(|a, b| a.len().cmp(&b.len()));
-        let smallest_cycle = &mut cycles[0];
+    Evaluation::Open(Box::new(Bounded {
+        gd,
+        gb,
+        forced,
+        folds,
+        position,
+        key,
+        k,
+        lower_bound,
+        candidate: best_solution,
+        values,
+    }))
+}
 
-        smallest_cycle.sort_unstable_by(|a, b| {
-            let a_key = gd.get_incoming(a).len() * gd.get_outgoing(a).len();
-            let b_key = gd.get_incoming(b).len() * gd.get_outgoing(b).len();
-            a_key.cmp(&b_key)
-        });
+fn branch_and_reduce(
+    graph: Graph,
+    upper_bound: usize,
+    position: Position,
+    search: &Search,
+    stack: Option<Stack>,
+) -> Option<Vec<u32>> {
+    // The results of a shared table would depend on what other workers have
+    // solved so far, so every sequential subtree of a parallel search looks
+    // up only its own results.
+    let (table, cuts);
+    let mut search = *search;
+    if search.parallel_depth > 0 && position.level == search.parallel_depth && search.table_limit > 0 {
+        table = Mutex::new(TranspositionTable::new(search.table_limit));
+        cuts = AtomicUsize::new(0);
+        search.table = Some(&table);
+        search.cuts = Some(&cuts);
+    }
+    let search = &search;
 
-        for vertex in smallest_cycle {
-            let mut gc = gd.clone() + gb.clone();
-            gc.remove_vertex(*vertex);
-            let solution = branch_and_reduce(gc, k - 1, freq, depth);
-            if let Some(mut dfvs) = solution {
-                dfvs.push(*vertex);
+    // the result is only stored if neither this node nor its descendants had
+    // their budget cut by the incumbent, as it could be any solution within
+    // the budget otherwise
+    let before = search.cuts();
 
-                // current solution matches the lower bound, it must be an optimal solution
-                if dfvs.len() == lower_bound {
-                    dfvs.append(&mut forced);
-                    return Some(dfvs);
+    let Bounded {
+        gd,
+        gb,
+        forced,
+        folds,
+        position,
+        key,
+        mut k,
+        lower_bound,
+        candidate: mut best_solution,
+        values,
+    } = match evaluate(graph, upper_bound, position, search, stack) {
+        Evaluation::Pruned => return None,
+        Evaluation::Solved(solution) => return Some(solution),
+        Evaluation::Open(node) => *node,
+    };
+
+    let branches = search.branch(position.level, &gd, &gb, &values);
+    if position.level < search.parallel_depth {
+        // The children share the budget of this node, and the smallest
+        // solution is taken. Among solutions of the same size, the one of the
+        // first branch is taken, whichever child finished first.
+        let solutions: Vec<_> = branches
+            .par_iter()
+            .map(|branch| solve_branch(&gd, &gb, branch, k, position, search, None))
+            .collect();
+        if let Some(dfvs) = solutions.into_iter().flatten().min_by_key(|dfvs| dfvs.len()) {
+            best_solution = Some(dfvs);
+        }
+        return best_solution.map(|solution| lift(solution, &forced, &folds));
+    }
+
+    let frame = stack.map(|stack| Frame {
+        gd: &gd,
+        gb: &gb,
+        forced: &forced,
+        folds: &folds,
+        position,
+        lower_bound,
+        branches: &branches,
+        next: Cell::new(0),
+        whole: false,
+        parent: stack.top,
+    });
+    let stack = stack.zip(frame.as_ref());
+    if let (Some((stack, frame)), Some(candidate)) = (stack, &best_solution) {
+        stack.trail.improve(frame, candidate);
+    }
+
+    let budget = k;
+    for (i, branch) in branches.iter().enumerate() {
+        let child_stack = stack.map(|(stack, frame)| {
+            stack.trail.checkpoint(frame);
+            frame.next.set(i + 1);
+            Stack {
+                top: Some(frame),
+                ..stack
+            }
+        });
+        if let Some(dfvs) = solve_branch(&gd, &gb, branch, k, position, search, child_stack) {
+            if let Some((stack, frame)) = stack {
+                stack.trail.improve(frame, &dfvs);
+            }
+            // current solution matches the lower bound, it must be an optimal solution
+            if dfvs.len() == lower_bound {
+                if search.cuts() == before {
+                    search.store_optimal(key, &dfvs);
                 }
-                k = dfvs.len() - 1; // look for a strictly better solution
-                best_solution = Some(dfvs);
+                return Some(lift(dfvs, &forced, &folds));
             }
+            k = dfvs.len() - 1; // look for a strictly better solution
+            best_solution = Some(dfvs);
+        }
 
-            // we are not going to find a solution better than the lower bound
-            if k <= lower_bound {
+        // other workers may have found a smaller solution in the meantime
+        k = match search.cap(position.taken, k) {
+            Some(k) => k,
+            None => break,
+        };
+
+        // we are not going to find a solution better than the lower bound
+        if k < lower_bound {
+            break;
+        }
+    }
+
+    if search.cuts() == before {
+        match &best_solution {
+            Some(solution) => search.store_optimal(key, solution),
+            None => search.store(key, Entry::LowerBound(budget + 1)),
+        }
+    }
+    best_solution.map(|solution| lift(solution, &forced, &folds))
+}
+
+/// Returns the graph of the child of `branch`, or `None` if the vertices it
+/// keeps contain a cycle.
+fn child(gd: &Graph, gb: &Graph, branch: &Branch) -> Option<Graph> {
+    let mut child = gd.clone() + gb.clone();
+    child.remove_vertices(&branch.take);
+    if !child.contract_undeletable(&branch.keep) {
+        return None;
+    }
+    Some(child)
+}
+
+/// Solves the child of `branch` within a budget of `upper_bound`, and returns
+/// its solution including the vertices taken by the branch.
+fn solve_branch(
+    gd: &Graph,
+    gb: &Graph,
+    branch: &Branch,
+    upper_bound: usize,
+    position: Position,
+    search: &Search,
+    stack: Option<Stack>,
+) -> Option<Vec<u32>> {
+    if branch.take.len() > upper_bound {
+        return None;
+    }
+
+    let child = child(gd, gb, branch)?;
+    let position = Position {
+        level: position.level + 1,
+        taken: position.taken + branch.take.len(),
+        ..position
+    };
+    let mut dfvs = branch_and_reduce(child, upper_bound - branch.take.len(), position, search, stack)?;
+    dfvs.extend_from_slice(&branch.take);
+    Some(dfvs)
+}
+
+/// A step on the path from the root of the best-first search to an open node.
+enum Step {
+    /// The vertices forced and folded by the reductions of an ancestor.
+    Reduced(Vec<u32>, Folds),
+
+    /// The vertices taken by a branch.
+    Branched(Vec<u32>),
+}
+
+/// The path to an open node, shared with the paths of its siblings.
+struct Path {
+    step: Step,
+    parent: Option<Rc<Path>>,
+}
+
+/// Lifts a solution of the graph of an open node to a solution of the root
+/// along the path to the node.
+fn lift_path(mut solution: Vec<u32>, mut path: Option<&Rc<Path>>) -> Vec<u32> {
+    while let Some(node) = path {
+        match &node.step {
+            Step::Reduced(forced, folds) => solution = lift(solution, forced, folds),
+            Step::Branched(take) => solution.extend_from_slice(take),
+        }
+        path = node.parent.as_ref();
+    }
+    solution
+}
+
+struct OpenNode {
+    graph: Graph,
+    position: Position,
+
+    /// Lower bound on the size of the solutions of the root within the node.
+    bound: usize,
+    path: Option<Rc<Path>>,
+}
+
+/// Returns a rough estimate of the number of bytes used by `graph`.
+fn estimated_size(graph: &Graph) -> usize {
+    let vertices = graph.total_vertices();
+    let arcs: usize = (0..vertices as u32)
+        .map(|v| graph.get_outgoing(&v).len())
+        .sum();
+    vertices * (2 * std::mem::size_of::<Vec<u32>>() + 2) + 2 * arcs * std::mem::size_of::<u32>()
+}
+
+/// The open nodes of the best-first search, ordered by their bounds. Among
+/// nodes with the same bound, deeper nodes come first, since they are closer
+/// to a solution.
+struct OpenNodes {
+    queue: Heap<MinItem>,
+    nodes: Vec<Option<OpenNode>>,
+    free: Vec<usize>,
+
+    /// The estimated number of bytes used by the open nodes.
+    memory: usize,
+    vertices: usize,
+}
+
+impl OpenNodes {
+    fn new(vertices: usize) -> OpenNodes {
+        OpenNodes {
+            queue: Heap::new(0),
+            nodes: Vec::new(),
+            free: Vec::new(),
+            memory: 0,
+            vertices,
+        }
+    }
+
+    fn root(graph: Graph) -> OpenNodes {
+        let mut open = OpenNodes::new(graph.total_vertices());
+        open.push(OpenNode {
+            graph,
+            position: Position::root(),
+            bound: 0,
+            path: None,
+        });
+        open
+    }
+
+    fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    fn push(&mut self, node: OpenNode) {
+        let priority = node.bound * (self.vertices + 1) + self.vertices - node.position.taken;
+        self.memory += estimated_size(&node.graph);
+        let slot = match self.free.pop() {
+            Some(slot) => {
+                self.nodes[slot] = Some(node);
+                slot
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        };
+        self.queue.insert(MinItem::new(slot, priority as i64));
+    }
+
+    fn pop(&mut self) -> Option<OpenNode> {
+        let slot = self.queue.extract_min()?.key();
+        let node = self.nodes[slot].take().unwrap();
+        self.free.push(slot);
+        self.memory -= estimated_size(&node.graph);
+        Some(node)
+    }
+
+    fn nodes(&self) -> Vec<&OpenNode> {
+        self.nodes.iter().flatten().collect()
+    }
+}
+
+/// Receives the state of a search for checkpoints.
+trait Checkpoint {
+    fn due(&self) -> bool;
+
+    /// Writes the open nodes and the best solution of the root found so far.
+    fn write(&mut self, open: &[&OpenNode], best: Option<&Vec<u32>>);
+}
+
+/// A node on the stack of a sequential search. Its branches from `next` on
+/// have not been explored yet, and become open nodes in a checkpoint.
+struct Frame<'a> {
+    gd: &'a Graph,
+    gb: &'a Graph,
+    forced: &'a [u32],
+    folds: &'a Folds,
+    position: Position,
+    lower_bound: usize,
+    branches: &'a [Branch],
+    next: Cell<usize>,
+
+    /// Whether the node is solved by a search of each of its components, in
+    /// which case the node itself becomes an open node instead of the nodes
+    /// below it.
+    whole: bool,
+    parent: Option<&'a Frame<'a>>,
+}
+
+impl Frame<'_> {
+    /// Returns the branch that is explored below this node.
+    fn current(&self) -> &Branch {
+        &self.branches[self.next.get() - 1]
+    }
+}
+
+/// A sequential search from an open node, whose stack is written to
+/// checkpoints along with the other open nodes.
+struct Trail<'a> {
+    open: &'a OpenNodes,
+
+    /// The path to the open node the search started from.
+    path: Option<Rc<Path>>,
+
+    /// The best solution of the root found so far.
+    best: RefCell<Option<Vec<u32>>>,
+    checkpoint: RefCell<&'a mut dyn Checkpoint>,
+}
+
+impl Trail<'_> {
+    /// Lifts a solution of the reduced graph of `frame` to a solution of the
+    /// root, unless `frame` lies below a node that is solved as a whole.
+    fn lift(&self, frame: &Frame, solution: Vec<u32>) -> Option<Vec<u32>> {
+        let mut solution = lift(solution, frame.forced, frame.folds);
+        let mut parent = frame.parent;
+        while let Some(frame) = parent {
+            if frame.whole {
+                return None;
+            }
+            solution.extend_from_slice(&frame.current().take);
+            solution = lift(solution, frame.forced, frame.folds);
+            parent = frame.parent;
+        }
+        Some(lift_path(solution, self.path.as_ref()))
+    }
+
+    /// Records a solution of the reduced graph of `frame`, which is always
+    /// smaller than the best solution so far.
+    fn improve(&self, frame: &Frame, solution: &[u32]) {
+        if let Some(solution) = self.lift(frame, solution.to_vec()) {
+            *self.best.borrow_mut() = Some(solution);
+        }
+    }
+
+    /// Writes a checkpoint if one is due, where `frame` is the top of the
+    /// stack. The open nodes are the unexplored branches of the nodes on the
+    /// stack, up to the first node that is solved as a whole, and the open
+    /// nodes outside of the search.
+    fn checkpoint(&self, frame: &Frame) {
+        let mut checkpoint = self.checkpoint.borrow_mut();
+        if !checkpoint.due() {
+            return;
+        }
+
+        let mut frames = Vec::new();
+        let mut current = Some(frame);
+        while let Some(frame) = current {
+            frames.push(frame);
+            current = frame.parent;
+        }
+        frames.reverse();
+        if let Some(whole) = frames.iter().position(|frame| frame.whole) {
+            frames.truncate(whole + 1);
+        }
+
+        let mut nodes = Vec::new();
+        let mut path = self.path.clone();
+        for (i, frame) in frames.iter().enumerate() {
+            let reduced = Some(Rc::new(Path {
+                step: Step::Reduced(frame.forced.to_vec(), frame.folds.clone()),
+                parent: path,
+            }));
+            let position = frame.position;
+            if frame.whole {
+                nodes.push(OpenNode {
+                    graph: frame.gd.clone() + frame.gb.clone(),
+                    position,
+                    bound: position.taken,
+                    path: reduced,
+                });
                 break;
             }
+
+            let bound = position.taken + frame.lower_bound;
+            for branch in &frame.branches[frame.next.get()..] {
+                if let Some(graph) = child(frame.gd, frame.gb, branch) {
+                    let taken = position.taken + branch.take.len();
+                    nodes.push(OpenNode {
+                        graph,
+                        position: Position {
+                            level: position.level + 1,
+                            taken,
+                            ..position
+                        },
+                        bound: bound.max(taken),
+                        path: Some(Rc::new(Path {
+                            step: Step::Branched(branch.take.clone()),
+                            parent: reduced.clone(),
+                        })),
+                    });
+                }
+            }
+
+            // the stack continues with the branch that is explored
+            path = (i + 1 < frames.len()).then(|| {
+                Rc::new(Path {
+                    step: Step::Branched(frame.current().take.clone()),
+                    parent: reduced,
+                })
+            });
         }
+
+        let mut open = self.open.nodes();
+        open.extend(&nodes);
+        checkpoint.write(&open, self.best.borrow().as_ref());
+    }
+}
+
+/// The stack of a sequential search that writes checkpoints.
+#[derive(Clone, Copy)]
+struct Stack<'s, 't> {
+    trail: &'s Trail<'t>,
+    top: Option<&'s Frame<'s>>,
+}
+
+/// Number of expanded nodes between two reports of the gap.
+const REPORT_INTERVAL: usize = 1000;
+
+/// Prints the global lower bound, the incumbent and the gap between them.
+fn report(expanded: usize, open: usize, lower_bound: usize, incumbent: usize) {
+    let gap = if incumbent == 0 {
+        0.
     } else {
-        let mut max_star = std::mem::take(&mut stars[0]);
-        for i in 1..stars.len() {
-            if stars[i].1.len() > max_star.1.len() {
-                max_star = std::mem::take(&mut stars[i]);
+        (incumbent - lower_bound) as f64 / incumbent as f64 * 100.
+    };
+    eprintln!(
+        "best-first: {} expanded, {} open, lower bound {}, incumbent {}, gap {:.2}%",
+        expanded, open, lower_bound, incumbent, gap
+    );
+}
+
+/// Best-first variant of [`branch_and_reduce`], which always expands the open
+/// node with the smallest lower bound and looks for solutions smaller than the
+/// incumbent of `search`. The bound of the expanded node is therefore a lower
+/// bound of the whole graph, and the gap to the incumbent is reported
+/// regularly. Once the open nodes use more than `memory_limit` bytes, the
+/// remaining nodes are solved depth-first in the order of their bounds.
+/// Before every expansion, the open nodes and the best solution found so far
+/// are handed to `checkpoint`.
+fn best_first(
+    mut open: OpenNodes,
+    search: &Search,
+    memory_limit: usize,
+    checkpoint: &mut dyn Checkpoint,
+) -> Option<Vec<u32>> {
+    let mut best_solution = None;
+    let mut depth_first = false;
+    let mut expanded = 0;
+    loop {
+        if checkpoint.due() {
+            checkpoint.write(&open.nodes(), best_solution.as_ref());
+        }
+        let node = match open.pop() {
+            Some(node) => node,
+            None => break,
+        };
+
+        let incumbent = search.incumbent.load(Ordering::Relaxed);
+        if node.bound >= incumbent {
+            continue;
+        }
+
+        expanded += 1;
+        if expanded % REPORT_INTERVAL == 0 {
+            report(expanded, open.len(), node.bound, incumbent);
+        }
+        if !depth_first && open.memory > memory_limit {
+            eprintln!("best-first: memory limit reached, continuing depth-first");
+            depth_first = true;
+        }
+
+        // look for a strictly better solution
+        let budget = incumbent - 1 - node.position.taken;
+        if depth_first {
+            solve_open_node(node, budget, &open, search, &mut best_solution, checkpoint);
+            continue;
+        }
+
+        let bounded = match evaluate(node.graph, budget, node.position, search, None) {
+            Evaluation::Pruned => continue,
+            Evaluation::Solved(solution) => {
+                best_solution = Some(lift_path(solution, node.path.as_ref()));
+                continue;
             }
+            Evaluation::Open(bounded) => bounded,
+        };
+
+        let position = bounded.position;
+        if let Some(candidate) = bounded.candidate {
+            let solution = lift(candidate, &bounded.forced, &bounded.folds);
+            best_solution = Some(lift_path(solution, node.path.as_ref()));
         }
-        let v = max_star.0;
-        let mut nv = max_star.1;
 
-        let mut gcv = gd.clone() + gb.clone();
-        gcv.remove_vertex(v);
-        let sv = branch_and_reduce(gcv, k - 1, freq, depth);
-        if let Some(mut dfvs) = sv {
-            dfvs.push(v);
-            if dfvs.len() == lower_bound {
-                dfvs.append(&mut forced);
-                return Some(dfvs);
+        let bound = position.taken + bounded.lower_bound;
+        let branches = search.branch(position.level, &bounded.gd, &bounded.gb, &bounded.values);
+        let reduced = Rc::new(Path {
+            step: Step::Reduced(bounded.forced, bounded.folds),
+            parent: node.path,
+        });
+        for branch in branches {
+            if branch.take.len() > bounded.k {
+                continue;
             }
-            k = dfvs.len() - 1;
-            best_solution = Some(dfvs);
+            let graph = match child(&bounded.gd, &bounded.gb, &branch) {
+                Some(graph) => graph,
+                None => continue,
+            };
+            let taken = position.taken + branch.take.len();
+            open.push(OpenNode {
+                graph,
+                position: Position {
+                    level: position.level + 1,
+                    taken,
+                    ..position
+                },
+                bound: bound.max(taken),
+                path: Some(Rc::new(Path {
+                    step: Step::Branched(branch.take),
+                    parent: Some(reduced.clone()),
+                })),
+            });
         }
+    }
 
-        if nv.len() <= k {
-            let mut gcnv = gd.clone() + gb.clone();
-            gcnv.remove_vertices(&nv);
+    let incumbent = search.incumbent.load(Ordering::Relaxed);
+    report(expanded, 0, incumbent, incumbent);
+    best_solution
+}
 
-            let snv = branch_and_reduce(gcnv, k - nv.len(), freq, depth);
-            if let Some(mut dfvs) = snv {
-                dfvs.append(&mut nv);
-                if dfvs.len() == lower_bound {
-                    dfvs.append(&mut forced);
-                    return Some(dfvs);
+/// Depth-first search from the open nodes, which are solved one after the
+/// other by [`branch_and_reduce`] in the order of their bounds, and look for
+/// solutions smaller than the incumbent of `search`.
+fn depth_first(mut open: OpenNodes, search: &Search, checkpoint: &mut dyn Checkpoint) -> Option<Vec<u32>> {
+    let mut best_solution = None;
+    while let Some(node) = open.pop() {
+        let incumbent = search.incumbent.load(Ordering::Relaxed);
+        if node.bound < incumbent {
+            let budget = incumbent - 1 - node.position.taken;
+            solve_open_node(node, budget, &open, search, &mut best_solution, checkpoint);
+        }
+    }
+    best_solution
+}
+
+/// Solves an open node depth-first within `budget`, and replaces
+/// `best_solution` by the solution of the root it finds. A sequential search
+/// hands its stack to `checkpoint` along with the other `open` nodes, while
+/// the workers of a parallel search do not write checkpoints.
+fn solve_open_node(
+    node: OpenNode,
+    budget: usize,
+    open: &OpenNodes,
+    search: &Search,
+    best_solution: &mut Option<Vec<u32>>,
+    checkpoint: &mut dyn Checkpoint,
+) {
+    let position = Position {
+        level: 0,
+        ..node.position
+    };
+    let trail = Trail {
+        open,
+        path: node.path,
+        best: RefCell::new(best_solution.take()),
+        checkpoint: RefCell::new(checkpoint),
+    };
+    let stack = (search.parallel_depth == 0).then_some(Stack {
+        trail: &trail,
+        top: None,
+    });
+    *best_solution = match branch_and_reduce(node.graph, budget, position, search, stack) {
+        Some(solution) => Some(lift_path(solution, trail.path.as_ref())),
+        None => trail.best.into_inner(),
+    };
+}
+
+/// Writes the open nodes and the paths leading to them, where every path is
+/// written once and refers to its parent by its position in the list.
+fn write_open_nodes(writer: &mut Writer, open: &[&OpenNode]) {
+    let mut ids: FxHashMap<*const Path, usize> = FxHashMap::default();
+    let mut paths: Vec<&Rc<Path>> = Vec::new();
+    for node in open {
+        // the ancestors of a path are written before the path itself
+        let mut unseen = Vec::new();
+        let mut path = node.path.as_ref();
+        while let Some(current) = path {
+            if ids.contains_key(&Rc::as_ptr(current)) {
+                break;
+            }
+            unseen.push(current);
+            path = current.parent.as_ref();
+        }
+        for current in unseen.into_iter().rev() {
+            paths.push(current);
+            ids.insert(Rc::as_ptr(current), paths.len());
+        }
+    }
+
+    let id = |path: &Option<Rc<Path>>| path.as_ref().map_or(0, |path| ids[&Rc::as_ptr(path)]);
+    writer.numbers(&[paths.len()]);
+    for path in &paths {
+        match &path.step {
+            Step::Reduced(forced, folds) => {
+                writer.numbers(&[0, id(&path.parent)]);
+                writer.vertices(forced);
+                let folds: Vec<_> = folds.folds().iter().flat_map(|(v, a, b)| [*v, *a, *b]).collect();
+                writer.vertices(&folds);
+            }
+            Step::Branched(take) => {
+                writer.numbers(&[1, id(&path.parent)]);
+                writer.vertices(take);
+            }
+        }
+    }
+
+    writer.numbers(&[open.len()]);
+    for node in open {
+        let position = node.position;
+        writer.numbers(&[position.depth, position.level, position.taken, node.bound, id(&node.path)]);
+        writer.graph(&node.graph);
+    }
+}
+
+fn read_open_nodes(reader: &mut Reader, vertices: usize) -> Result<OpenNodes> {
+    // a path may only refer to the paths before it
+    let path = |paths: &[Rc<Path>], id: usize| match id {
+        0 => Ok(None),
+        id if id <= paths.len() => Ok(Some(paths[id - 1].clone())),
+        _ => Err(invalid(&format!("no path {}", id))),
+    };
+
+    let mut paths: Vec<Rc<Path>> = Vec::new();
+    for _ in 0..reader.number()? {
+        let header = reader.numbers(2)?;
+        let parent = path(&paths, header[1])?;
+        let step = match header[0] {
+            0 => {
+                let forced = reader.vertices()?;
+                let folds = reader.vertices()?;
+                if folds.len() % 3 != 0 {
+                    return Err(invalid("a fold needs three vertices"));
                 }
-                best_solution = Some(dfvs);
+                let folds = folds.chunks(3).map(|fold| (fold[0], fold[1], fold[2])).collect();
+                Step::Reduced(forced, Folds::from_folds(folds))
             }
+            1 => Step::Branched(reader.vertices()?),
+            step => return Err(invalid(&format!("unknown step {}", step))),
+        };
+        paths.push(Rc::new(Path { step, parent }));
+    }
+
+    let mut open = OpenNodes::new(vertices);
+    for _ in 0..reader.number()? {
+        let header = reader.numbers(5)?;
+        let graph = reader.graph()?;
+        if graph.total_vertices() != vertices {
+            return Err(invalid("an open node has the wrong number of vertices"));
         }
+        open.push(OpenNode {
+            graph,
+            position: Position {
+                depth: header[0],
+                level: header[1],
+                taken: header[2],
+            },
+            bound: header[3],
+            path: path(&paths, header[4])?,
+        });
     }
+    Ok(open)
+}
 
-    if let Some(result) = best_solution.as_mut() {
-        result.append(&mut forced);
+/// Returns the subgraphs induced by the strongly connected components that
+/// contain a cycle.
+fn cyclic_components(graph: &Graph) -> Vec<Graph> {
+    let mut components = Vec::new();
+    for component in graph.tarjan(true).unwrap() {
+        let first = component[0];
+        if component.len() > 1 || graph.get_outgoing(&first).contains(&first) {
+            components.push(graph.induced_subgraph(component));
+        }
     }
-    best_solution
+    components
+}
+
+/// Solves independent components within a combined budget of `upper_bound`.
+/// Every component may use its own lower bound plus the slack left by the
+/// lower bounds of the other components, and falls back to its heuristic
+/// upper bound if that already fits. Each component is a search of its own,
+/// since its solutions are only part of a solution of the whole graph.
+fn solve_components(
+    components: Vec<Graph>,
+    upper_bound: usize,
+    position: Position,
+    search: &Search,
+    stack: Option<Stack>,
+) -> Option<Vec<u32>> {
+    let mut lower_bounds = Vec::with_capacity(components.len());
+    for component in &components {
+        let (gd, gb) = component.clone().split();
+        lower_bounds.push(lower_bound(&gd, &gb, search.fresh()).0);
+    }
+
+    let total: usize = lower_bounds.iter().sum();
+    if total > upper_bound {
+        return None;
+    }
+    let mut slack = upper_bound - total;
+
+    let mut solution = Vec::new();
+    for (component, lower_bound) in components.into_iter().zip(lower_bounds) {
+        let budget = lower_bound + slack;
+        let ub = SimulatedAnnealing::upper_bound(&component);
+        let fits = ub.len() <= budget;
+        let k = if fits { ub.len() - 1 } else { budget };
+
+        let incumbent = AtomicUsize::new(k + 1);
+        let component_search = Search {
+            root_level: position.level,
+            root_budget: k,
+            incumbent: &incumbent,
+            ..*search
+        };
+        let position = Position { taken: 0, ..position };
+        let result = branch_and_reduce(component, k, position, &component_search, stack);
+
+        let mut sub_solution = if fits { result.unwrap_or(ub) } else { result? };
+        slack = budget - sub_solution.len();
+        solution.append(&mut sub_solution);
+    }
+    Some(solution)
 }
 
 fn branch_and_bound(graph: Graph, upper_bound: usize) -> Option<Vec<u32>> {
@@ -134,7 +1100,7 @@ fn branch_and_bound(graph: Graph, upper_bound: usize) -> Option<Vec<u32>> {
     let mut best_solution = None;
     let (gd, gb) = graph.split();
 
-    let (lower_bound, candidate) = lower::lower_bound(&gd, &gb);
+    let (lower_bound, candidate, _) = lower::lower_bound(&gd, &gb);
     if lower_bound > k {
         return None;
     }
@@ -219,18 +1185,177 @@ fn branch_and_bound(graph: Graph, upper_bound: usize) -> Option<Vec<u32>> {
     best_solution
 }
 
-pub fn solve(graph: &mut Graph, config: &Config) -> Vec<u32> {
-    let mut solution = Vec::new();
+/// Name of the state of this algorithm in checkpoints.
+const STATE: &str = "bnr";
+
+/// The state of [`solve`] in a checkpoint.
+struct State {
+    solved: usize,
+    solution: Vec<u32>,
+
+    /// The incumbent of the component being solved, followed by its open
+    /// nodes.
+    component: Option<(Vec<u32>, Reader)>,
+}
+
+/// Reads the state up to the open nodes, which are read by the worker that
+/// solves the component.
+fn read_state(mut reader: Reader, components: usize) -> Result<State> {
+    let solved = reader.number()?;
+    if solved > components {
+        return Err(invalid("more components are solved than there are"));
+    }
+    let solution = reader.vertices()?;
+    let incumbent = reader.vertices()?;
+    let component = (!incumbent.is_empty()).then_some((incumbent, reader));
+    Ok(State {
+        solved,
+        solution,
+        component,
+    })
+}
+
+/// Writes the checkpoints of the search of the component `index`, after the
+/// solution of the components before it.
+struct ComponentCheckpoint<'a> {
+    checkpointer: &'a mut Checkpointer,
+    index: usize,
+    solution: &'a [u32],
+
+    /// The incumbent of the component when its search started.
+    ub: &'a [u32],
+}
+
+impl Checkpoint for ComponentCheckpoint<'_> {
+    fn due(&self) -> bool {
+        self.checkpointer.due()
+    }
+
+    fn write(&mut self, open: &[&OpenNode], best: Option<&Vec<u32>>) {
+        let (index, solution, ub) = (self.index, self.solution, self.ub);
+        let result = self.checkpointer.write(STATE, |writer| {
+            writer.numbers(&[index]);
+            writer.vertices(solution);
+            writer.vertices(best.map_or(ub, Vec::as_slice));
+            write_open_nodes(writer, open);
+        });
+        if let Err(err) = result {
+            eprintln!("failed to write checkpoint: {}", err);
+        }
+    }
+}
+
+/// Solves the strongly connected components of `graph` one after the other.
+/// A checkpoint holds the number of solved components and their solution,
+/// then the incumbent and the open nodes of the current component, or an
+/// empty line between two components. The open nodes of the depth-first
+/// search are the unexplored branches on its stack, so both strategies resume
+/// where they stopped. A damaged state is reported and the graph is solved
+/// from the start.
+pub fn solve(graph: &mut Graph, config: &Config, checkpointer: &mut Checkpointer) -> Vec<u32> {
+    let rule = branching::rule(config.branching(), config.symmetry());
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(config.threads())
+        .build()
+        .unwrap();
+    // a few levels more than needed to keep every thread busy
+    let parallel_depth = if config.threads() > 1 {
+        (usize::BITS - config.threads().leading_zeros()) as usize + 2
+    } else {
+        0
+    };
+
+    // A limit of 0 disables the transposition table. A parallel search splits
+    // the limit among the tables of the subtrees its workers solve at a time.
+    let table_limit = config.table_limit() << 20;
+    let table = Mutex::new(TranspositionTable::new(table_limit));
+    let table = if table_limit > 0 && parallel_depth == 0 {
+        Some(&table)
+    } else {
+        None
+    };
+    let table_limit = if parallel_depth > 0 {
+        table_limit / config.threads()
+    } else {
+        0
+    };
+    let cuts = AtomicUsize::new(0);
+
     let components = graph.tarjan(true).unwrap();
-    for component in components {
+    let mut solution = Vec::new();
+    let mut solved = 0;
+    let mut resumed = None;
+    if let Some(reader) = checkpointer.take_state(STATE) {
+        match read_state(reader, components.len()) {
+            Ok(state) => {
+                solved = state.solved;
+                solution = state.solution;
+                resumed = state.component;
+            }
+            Err(err) => eprintln!("{}, solving from the start", err),
+        }
+    }
+
+    let initial = super::initial_solution(config, graph.total_vertices());
+    for (index, component) in components.into_iter().enumerate().skip(solved) {
         let subgraph = graph.induced_subgraph(component);
 
-        let mut ub = SimulatedAnnealing::upper_bound(&subgraph);
+        // only the first component after the solved ones can be resumed
+        let resumed = resumed.take().filter(|(incumbent, _)| {
+            let valid = subgraph.is_acyclic_with_fvs(incumbent);
+            if !valid {
+                eprintln!("damaged checkpoint: the incumbent is not a DFVS, solving the component from the start");
+            }
+            valid
+        });
+        let (mut ub, resumed) = match resumed {
+            Some((incumbent, reader)) => (incumbent, Some(reader)),
+            None => {
+                let ub = match &initial {
+                    Some(solution) => SimulatedAnnealing::upper_bound_from(&subgraph, solution),
+                    None => SimulatedAnnealing::upper_bound(&subgraph),
+                };
+                (ub, None)
+            }
+        };
+
         match config.algorithm() {
             Algorithm::BNR => {
-                if let Some(mut sub_solution) =
-                    branch_and_reduce(subgraph, ub.len() - 1, config.frequency(), 0)
-                {
+                let incumbent = AtomicUsize::new(ub.len());
+                let search = Search {
+                    freq: config.frequency(),
+                    rule: rule.as_ref(),
+                    root_level: 0,
+                    root_budget: ub.len() - 1,
+                    incumbent: &incumbent,
+                    parallel_depth,
+                    table,
+                    table_limit,
+                    cuts: Some(&cuts),
+                };
+                let memory_limit = config.memory_limit() << 20;
+                let mut checkpoint = ComponentCheckpoint {
+                    checkpointer: &mut *checkpointer,
+                    index,
+                    solution: &solution,
+                    ub: &ub,
+                };
+                let result = pool.install(|| {
+                    let vertices = subgraph.total_vertices();
+                    let open = match resumed.map(|mut state| read_open_nodes(&mut state, vertices)) {
+                        Some(Ok(open)) => open,
+                        Some(Err(err)) => {
+                            eprintln!("{}, solving the component from the start", err);
+                            OpenNodes::root(subgraph)
+                        }
+                        None => OpenNodes::root(subgraph),
+                    };
+                    match config.strategy() {
+                        Strategy::Dfs => depth_first(open, &search, &mut checkpoint),
+                        Strategy::BestFirst => best_first(open, &search, memory_limit, &mut checkpoint),
+                    }
+                });
+                if let Some(mut sub_solution) = result {
                     solution.append(&mut sub_solution);
                 } else {
                     solution.append(&mut ub);
@@ -245,6 +1370,17 @@ pub fn solve(graph: &mut Graph, config: &Config) -> Vec<u32> {
             }
             _ => panic!("should not happen"),
         }
+
+        if checkpointer.due() {
+            let result = checkpointer.write(STATE, |writer| {
+                writer.numbers(&[index + 1]);
+                writer.vertices(&solution);
+                writer.vertices(&[]);
+            });
+            if let Err(err) = result {
+                eprintln!("failed to write checkpoint: {}", err);
+            }
+        }
     }
 
     solution
@@ -253,6 +1389,16 @@ pub fn solve(graph: &mut Graph, config: &Config) -> Vec<u32> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::exact::branching::{LpFractional, StarCycle};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    impl Checkpoint for () {
+        fn due(&self) -> bool {
+            false
+        }
+
+        fn write(&mut self, _: &[&OpenNode], _: Option<&Vec<u32>>) {}
+    }
 
     fn generate_clique(vertices: usize) -> Graph {
         let mut graph = Graph::new(vertices);
@@ -265,11 +1411,27 @@ mod tests {
         graph
     }
 
+    fn clique_search(graph: Graph, upper_bound: usize, parallel_depth: usize) -> Option<Vec<u32>> {
+        let incumbent = AtomicUsize::new(upper_bound + 1);
+        let search = Search {
+            freq: 0,
+            rule: &StarCycle {},
+            root_level: 0,
+            root_budget: upper_bound,
+            incumbent: &incumbent,
+            parallel_depth,
+            table: None,
+            table_limit: 0,
+            cuts: None,
+        };
+        branch_and_reduce(graph, upper_bound, Position::root(), &search, None)
+    }
+
     #[test]
     fn branch_and_reduce_test_001() {
         let n = 3;
         let graph = generate_clique(n);
-        let solution = branch_and_reduce(graph.clone(), n, 0, 0).unwrap();
+        let solution = clique_search(graph.clone(), n, 0).unwrap();
         assert_eq!(solution.len(), n - 1);
         assert!(graph.is_acyclic_with_fvs(&solution));
     }
@@ -278,7 +1440,7 @@ mod tests {
     fn branch_and_reduce_test_002() {
         let n = 4;
         let graph = generate_clique(n);
-        let solution = branch_and_reduce(graph.clone(), n, 0, 0).unwrap();
+        let solution = clique_search(graph.clone(), n, 0).unwrap();
         assert_eq!(solution.len(), n - 1);
         assert!(graph.is_acyclic_with_fvs(&solution));
     }
@@ -287,8 +1449,224 @@ mod tests {
     fn branch_and_reduce_test_003() {
         let n = 5;
         let graph = generate_clique(n);
-        let solution = branch_and_reduce(graph.clone(), n, 0, 0).unwrap();
+        let solution = clique_search(graph.clone(), n, 0).unwrap();
+        assert_eq!(solution.len(), n - 1);
+        assert!(graph.is_acyclic_with_fvs(&solution));
+    }
+
+    #[test]
+    fn branch_and_reduce_test_004() {
+        // the parallel search returns the same solution as the sequential one
+        let n = 5;
+        let graph = generate_clique(n);
+        let sequential = clique_search(graph.clone(), n, 0).unwrap();
+        let parallel = clique_search(graph, n, 3).unwrap();
+        assert_eq!(sequential, parallel);
+    }
+
+    fn parallel_search(graph: Graph, threads: usize, parallel_depth: usize) -> Option<Vec<u32>> {
+        let pool = ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+        let upper_bound = graph.vertices();
+        let incumbent = AtomicUsize::new(upper_bound + 1);
+        let search = Search {
+            freq: 1,
+            rule: &LpFractional {},
+            root_level: 0,
+            root_budget: upper_bound,
+            incumbent: &incumbent,
+            parallel_depth,
+            table: None,
+            table_limit: 1 << 20,
+            cuts: None,
+        };
+        pool.install(|| branch_and_reduce(graph, upper_bound, Position::root(), &search, None))
+    }
+
+    #[test]
+    fn branch_and_reduce_test_005() {
+        // repeated parallel searches return the same solution on graphs with
+        // many optimal solutions
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..30 {
+            let n = rng.gen_range(10..22);
+            let p = rng.gen_range(0.15..0.35);
+            let mut graph = Graph::new(n);
+            for u in 0..n as u32 {
+                for v in 0..n as u32 {
+                    if u != v && rng.gen_bool(p) {
+                        graph.add_arc(u, v);
+                    }
+                }
+            }
+            let first = parallel_search(graph.clone(), 6, 4).unwrap();
+            assert!(graph.is_acyclic_with_fvs(&first));
+            for _ in 0..2 {
+                assert_eq!(parallel_search(graph.clone(), 6, 4), Some(first.clone()));
+            }
+        }
+    }
+
+    #[test]
+    fn branch_and_reduce_test_006() {
+        // the workers prune with the solutions of each other, and the solution
+        // does not depend on the number of workers
+        let mut rng = StdRng::seed_from_u64(4);
+        for _ in 0..30 {
+            let n = rng.gen_range(10..22);
+            let p = rng.gen_range(0.15..0.35);
+            let mut graph = Graph::new(n);
+            for u in 0..n as u32 {
+                for v in 0..n as u32 {
+                    if u != v && rng.gen_bool(p) {
+                        graph.add_arc(u, v);
+                    }
+                }
+            }
+            let sequential = parallel_search(graph.clone(), 1, 0).unwrap();
+            let first = parallel_search(graph.clone(), 1, 2).unwrap();
+            assert_eq!(first.len(), sequential.len());
+            for (threads, parallel_depth) in [(2, 3), (6, 5), (32, 7)] {
+                assert_eq!(parallel_search(graph.clone(), threads, parallel_depth), Some(first.clone()));
+            }
+        }
+    }
+
+    fn clique_best_first(graph: Graph, upper_bound: usize, memory_limit: usize) -> Option<Vec<u32>> {
+        let incumbent = AtomicUsize::new(upper_bound + 1);
+        let search = Search {
+            freq: 0,
+            rule: &StarCycle {},
+            root_level: 0,
+            root_budget: upper_bound,
+            incumbent: &incumbent,
+            parallel_depth: 0,
+            table: None,
+            table_limit: 0,
+            cuts: None,
+        };
+        best_first(OpenNodes::root(graph), &search, memory_limit, &mut ())
+    }
+
+    #[test]
+    fn best_first_test_001() {
+        let n = 5;
+        let graph = generate_clique(n);
+        let solution = clique_best_first(graph.clone(), n, usize::MAX).unwrap();
+        assert_eq!(solution.len(), n - 1);
+        assert!(graph.is_acyclic_with_fvs(&solution));
+    }
+
+    #[test]
+    fn best_first_test_002() {
+        // without memory, the search continues depth-first from the root
+        let n = 5;
+        let graph = generate_clique(n);
+        let solution = clique_best_first(graph.clone(), n, 0).unwrap();
         assert_eq!(solution.len(), n - 1);
         assert!(graph.is_acyclic_with_fvs(&solution));
     }
+
+    /// Keeps the checkpoint with the given number, in the format of [`solve`].
+    struct Recorder {
+        written: usize,
+        keep: usize,
+        state: Option<String>,
+    }
+
+    impl Checkpoint for Recorder {
+        fn due(&self) -> bool {
+            self.state.is_none()
+        }
+
+        fn write(&mut self, open: &[&OpenNode], best: Option<&Vec<u32>>) {
+            self.written += 1;
+            if self.written == self.keep {
+                let mut writer = Writer::new();
+                writer.vertices(best.map_or(&[], Vec::as_slice));
+                write_open_nodes(&mut writer, open);
+                self.state = Some(writer.finish());
+            }
+        }
+    }
+
+    fn depth_first_search(open: OpenNodes, upper_bound: usize, checkpoint: &mut dyn Checkpoint) -> Option<Vec<u32>> {
+        let incumbent = AtomicUsize::new(upper_bound + 1);
+        let search = Search {
+            freq: 1,
+            rule: &LpFractional {},
+            root_level: 0,
+            root_budget: upper_bound,
+            incumbent: &incumbent,
+            parallel_depth: 0,
+            table: None,
+            table_limit: 0,
+            cuts: None,
+        };
+        depth_first(open, &search, checkpoint)
+    }
+
+    #[test]
+    fn depth_first_test_001() {
+        // a search resumed from a checkpoint inside the search finds an
+        // optimal solution as well
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..20 {
+            let n = rng.gen_range(10..20);
+            let p = rng.gen_range(0.15..0.35);
+            let mut graph = Graph::new(n);
+            for u in 0..n as u32 {
+                for v in 0..n as u32 {
+                    if u != v && rng.gen_bool(p) {
+                        graph.add_arc(u, v);
+                    }
+                }
+            }
+            let optimal = depth_first_search(OpenNodes::root(graph.clone()), n, &mut ()).unwrap();
+
+            for keep in 1..6 {
+                let mut recorder = Recorder {
+                    written: 0,
+                    keep,
+                    state: None,
+                };
+                depth_first_search(OpenNodes::root(graph.clone()), n, &mut recorder);
+                let state = match recorder.state {
+                    Some(state) => state,
+                    None => break,
+                };
+
+                let mut reader = Reader::new(&state);
+                let best = reader.vertices().unwrap();
+                let open = read_open_nodes(&mut reader, n).unwrap();
+                let upper_bound = if best.is_empty() { n } else { best.len() - 1 };
+                let solution = depth_first_search(open, upper_bound, &mut ()).unwrap_or(best);
+                assert_eq!(solution.len(), optimal.len());
+                assert!(graph.is_acyclic_with_fvs(&solution));
+            }
+        }
+    }
+
+    #[test]
+    fn read_open_nodes_test_001() {
+        // a path that refers to a later path is an error
+        let mut reader = Reader::new("1\n1 2\n1\n0\n");
+        assert!(read_open_nodes(&mut reader, 3).is_err());
+    }
+
+    #[test]
+    fn cyclic_components_test_001() {
+        // a triangle, a 2-cycle and an acyclic tail
+        let mut graph = Graph::new(7);
+        graph.add_arc(0, 1);
+        graph.add_arc(1, 2);
+        graph.add_arc(2, 0);
+        graph.add_arc(3, 4);
+        graph.add_arc(4, 3);
+        graph.add_arc(4, 5);
+        graph.add_arc(5, 6);
+        let components = cyclic_components(&graph);
+        assert_eq!(components.len(), 2);
+        let sizes: Vec<_> = components.iter().map(|c| c.vertices()).collect();
+        assert!(sizes.contains(&3) && sizes.contains(&2));
+    }
 }
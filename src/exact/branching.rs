@@ -0,0 +1,359 @@
+//! Branching rules for the branch-and-reduce algorithm. A rule splits the
+//! current instance, given as the directed graph `gd` and the undirected graph
+//! `gb` of the split, into children whose solutions together contain an
+//! optimal solution of the instance.
+use crate::{
+    graph::{EdgeCycleCover, Graph, Symmetry},
+    io::Branching,
+    util::algorithms::{difference, intersection},
+};
+
+/// A child of a branching step, which takes the vertices `take` into the
+/// solution and keeps the vertices `keep` out of the solution.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Branch {
+    pub take: Vec<u32>,
+    pub keep: Vec<u32>,
+}
+
+impl Branch {
+    /// Creates a branch, `take` is sorted since `Graph::remove_vertices`
+    /// expects a sorted list.
+    fn new(mut take: Vec<u32>, keep: Vec<u32>) -> Branch {
+        take.sort_unstable();
+        Branch { take, keep }
+    }
+}
+
+pub trait BranchingRule: Sync {
+    /// Returns the children of the instance `gd` and `gb`, where `values` are
+    /// the values of the vertices in the optimal LP relaxation.
+    fn branch(&self, gd: &Graph, gb: &Graph, values: &[f64]) -> Vec<Branch>;
+
+    /// Returns the children of the root of a search, where rules may spend
+    /// more time than at the other nodes.
+    fn branch_root(&self, gd: &Graph, gb: &Graph, values: &[f64]) -> Vec<Branch> {
+        self.branch(gd, gb, values)
+    }
+}
+
+pub fn rule(branching: &Branching, symmetry: bool) -> Box<dyn BranchingRule> {
+    let rule: Box<dyn BranchingRule> = match branching {
+        Branching::StarCycle => Box::new(StarCycle {}),
+        Branching::MaxDegree => Box::new(MaxDegree {}),
+        Branching::MostCycles => Box::new(MostCycles {}),
+        Branching::LpFractional => Box::new(LpFractional {}),
+    };
+    if symmetry {
+        Box::new(Orbital { inner: rule })
+    } else {
+        rule
+    }
+}
+
+/// Branches on a vertex: either it is in the solution, or it is not.
+fn binary(vertex: u32) -> Vec<Branch> {
+    vec![
+        Branch::new(vec![vertex], vec![]),
+        Branch::new(vec![], vec![vertex]),
+    ]
+}
+
+/// Returns whether `vertex` has no arcs in the directed graph of the split.
+fn is_undirected_only(gd: &Graph, vertex: u32) -> bool {
+    gd.get_outgoing(&vertex).is_empty() && gd.get_incoming(&vertex).is_empty()
+}
+
+/// Returns the mirrors of `vertex`: the vertices *u* at distance 2 in `gb`
+/// for which *N(vertex) \ N(u)* is a clique. If some optimal solution
+/// contains `vertex`, then some optimal solution contains `vertex` and all its
+/// mirrors. The exchange argument replaces `vertex` by a neighbour, so it
+/// only holds if `vertex` has no directed arcs.
+fn mirrors(gd: &Graph, gb: &Graph, vertex: u32) -> Vec<u32> {
+    if !is_undirected_only(gd, vertex) {
+        return Vec::new();
+    }
+
+    let neighbors = gb.get_outgoing(&vertex);
+    let mut candidates = Vec::new();
+    for w in neighbors {
+        for u in gb.get_outgoing(w) {
+            if *u != vertex && neighbors.binary_search(u).is_err() {
+                candidates.push(*u);
+            }
+        }
+    }
+    candidates.sort_unstable();
+    candidates.dedup();
+
+    candidates
+        .into_iter()
+        .filter(|u| {
+            let rest = difference(neighbors, gb.get_outgoing(u));
+            rest.iter().enumerate().all(|(i, a)| {
+                let adjacent = gb.get_outgoing(a);
+                rest[i + 1..].iter().all(|b| adjacent.binary_search(b).is_ok())
+            })
+        })
+        .collect()
+}
+
+/// Returns the satellites of `vertex`: the vertices *u* for which there is a
+/// neighbour *w* with *N[w] \ N[vertex] = {u}* in `gb`. If some optimal
+/// solution avoids `vertex`, then some optimal solution avoids `vertex` and
+/// all its satellites. The exchange argument replaces *w* by `vertex`, so it
+/// only holds if *w* has no directed arcs.
+fn satellites(gd: &Graph, gb: &Graph, vertex: u32) -> Vec<u32> {
+    let neighbors = gb.get_outgoing(&vertex);
+    let mut satellites = Vec::new();
+    for w in neighbors {
+        if !is_undirected_only(gd, *w) {
+            continue;
+        }
+        let outside: Vec<_> = difference(gb.get_outgoing(w), neighbors)
+            .into_iter()
+            .filter(|u| *u != vertex)
+            .collect();
+        if outside.len() == 1 {
+            satellites.push(outside[0]);
+        }
+    }
+    satellites.sort_unstable();
+    satellites.dedup();
+    satellites
+}
+
+/// Branches on the undirected neighbourhood of `vertex`: either take `vertex`
+/// or keep it and take all its undirected neighbours. Mirrors are taken
+/// together with `vertex` if there are any, and satellites are kept together
+/// with `vertex` otherwise.
+fn star_branch(gd: &Graph, gb: &Graph, vertex: u32) -> Vec<Branch> {
+    let neighbors = gb.get_outgoing(&vertex).to_vec();
+    let mut take = mirrors(gd, gb, vertex);
+    if !take.is_empty() {
+        take.push(vertex);
+        return vec![Branch::new(take, vec![]), Branch::new(neighbors, vec![vertex])];
+    }
+
+    let mut keep = satellites(gd, gb, vertex);
+    let mut take = neighbors;
+    for u in &keep {
+        take.extend_from_slice(gb.get_outgoing(u));
+    }
+    take.sort_unstable();
+    take.dedup();
+    keep.push(vertex);
+    keep.sort_unstable();
+
+    // adjacent satellites cannot all be kept, so only the first child remains
+    if !intersection(&take, &keep).is_empty() {
+        return vec![Branch::new(vec![vertex], vec![])];
+    }
+    vec![Branch::new(vec![vertex], vec![]), Branch::new(take, keep)]
+}
+
+/// Branches on the vertex with the most undirected neighbours, refined with
+/// mirrors and satellites, see [`star_branch`]. Without undirected edges,
+/// branches on the shortest cycle of the directed graph, where child *i*
+/// takes vertex *i* and keeps the vertices before it.
+pub struct StarCycle {}
+impl BranchingRule for StarCycle {
+    fn branch(&self, gd: &Graph, gb: &Graph, _: &[f64]) -> Vec<Branch> {
+        let stars = gb.stars();
+        if let Some((v, _)) = stars.into_iter().rev().max_by_key(|(_, nv)| nv.len()) {
+            return star_branch(gd, gb, v);
+        }
+
+        let cycles = gd.edge_cycle_cover();
+        let mut smallest_cycle = match cycles.into_iter().min_by_key(|cycle| cycle.len()) {
+            Some(cycle) => cycle,
+            None => return Vec::new(),
+        };
+        smallest_cycle.sort_unstable_by_key(|v| gd.get_incoming(v).len() * gd.get_outgoing(v).len());
+
+        let mut branches = Vec::with_capacity(smallest_cycle.len());
+        for i in 0..smallest_cycle.len() {
+            branches.push(Branch::new(
+                vec![smallest_cycle[i]],
+                smallest_cycle[..i].to_vec(),
+            ));
+        }
+        branches
+    }
+}
+
+/// Branches on the vertex with the largest degree.
+pub struct MaxDegree {}
+impl BranchingRule for MaxDegree {
+    fn branch(&self, gd: &Graph, gb: &Graph, _: &[f64]) -> Vec<Branch> {
+        let mut graph = gd.clone() + gb.clone();
+        binary(graph.max_degree_vertex())
+    }
+}
+
+/// Branches on the vertex that is hit by the most cycles of an edge cycle
+/// cover.
+pub struct MostCycles {}
+impl BranchingRule for MostCycles {
+    fn branch(&self, gd: &Graph, gb: &Graph, _: &[f64]) -> Vec<Branch> {
+        let graph = gd.clone() + gb.clone();
+        let mut hits = vec![0; graph.total_vertices()];
+        for cycle in graph.edge_cycle_cover() {
+            for vertex in cycle {
+                hits[vertex as usize] += 1;
+            }
+        }
+        let vertex = (0..hits.len()).max_by_key(|v| hits[*v]).unwrap();
+        binary(vertex as u32)
+    }
+}
+
+/// Branches on the vertex whose LP value is closest to 1/2, and prefers
+/// larger values among equally fractional vertices.
+pub struct LpFractional {}
+impl BranchingRule for LpFractional {
+    fn branch(&self, gd: &Graph, gb: &Graph, values: &[f64]) -> Vec<Branch> {
+        let graph = gd.clone() + gb.clone();
+        let mut best = None;
+        let mut best_key = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for (v, value) in values.iter().enumerate().take(graph.total_vertices()) {
+            if graph.get_outgoing(&(v as u32)).is_empty() || graph.get_incoming(&(v as u32)).is_empty() {
+                continue;
+            }
+            let key = (value.min(1. - value), *value);
+            if key > best_key {
+                best_key = key;
+                best = Some(v as u32);
+            }
+        }
+        match best {
+            Some(vertex) => binary(vertex),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Orbital branching: if the instance has an orbit *O* of vertices with arcs,
+/// either the first vertex of the largest such orbit is taken, or all of *O*
+/// is kept. If some optimal solution meets *O*, an automorphism maps it to one
+/// that contains the first vertex. The orbits are only computed at the root of
+/// a search, and without symmetry or below the root, `inner` branches.
+pub struct Orbital {
+    inner: Box<dyn BranchingRule>,
+}
+impl BranchingRule for Orbital {
+    fn branch(&self, gd: &Graph, gb: &Graph, values: &[f64]) -> Vec<Branch> {
+        self.inner.branch(gd, gb, values)
+    }
+
+    fn branch_root(&self, gd: &Graph, gb: &Graph, values: &[f64]) -> Vec<Branch> {
+        let graph = gd.clone() + gb.clone();
+        let orbit = graph
+            .orbits()
+            .into_iter()
+            .filter(|orbit| !graph.get_outgoing(&orbit[0]).is_empty())
+            .max_by_key(|orbit| orbit.len());
+        match orbit {
+            Some(orbit) => vec![
+                Branch::new(vec![orbit[0]], vec![]),
+                Branch::new(vec![], orbit),
+            ],
+            None => self.inner.branch(gd, gb, values),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn star_cycle_test_001() {
+        // a directed triangle without undirected edges
+        let mut gd = Graph::new(3);
+        gd.add_arc(0, 1);
+        gd.add_arc(1, 2);
+        gd.add_arc(2, 0);
+        let gb = Graph::new(3);
+        let branches = StarCycle {}.branch(&gd, &gb, &[]);
+        assert_eq!(branches.len(), 3);
+        for (i, branch) in branches.iter().enumerate() {
+            assert_eq!(branch.take.len(), 1);
+            assert_eq!(branch.keep.len(), i);
+        }
+    }
+
+    fn undirected(vertices: usize, edges: &[(u32, u32)]) -> Graph {
+        let mut graph = Graph::new(vertices);
+        for (u, v) in edges {
+            graph.add_arc(*u, *v);
+            graph.add_arc(*v, *u);
+        }
+        graph
+    }
+
+    #[test]
+    fn star_cycle_test_002() {
+        // a 4-cycle 0 - 1 - 2 - 3 - 0 with an extra vertex 4 at 0, so 2 is a
+        // mirror of 0
+        let gb = undirected(5, &[(0, 1), (1, 2), (2, 3), (3, 0), (0, 4)]);
+        let gd = Graph::new(5);
+        assert_eq!(mirrors(&gd, &gb, 0), vec![2]);
+        let branches = StarCycle {}.branch(&gd, &gb, &[]);
+        assert_eq!(branches[0], Branch::new(vec![0, 2], vec![]));
+        assert_eq!(branches[1], Branch::new(vec![1, 3, 4], vec![0]));
+
+        // a directed arc at 0 invalidates the mirror
+        let mut gd = Graph::new(5);
+        gd.add_arc(0, 4);
+        assert!(mirrors(&gd, &gb, 0).is_empty());
+    }
+
+    #[test]
+    fn star_cycle_test_003() {
+        // 0 is adjacent to 1, 2 and 3, and 1 has the only outside neighbour
+        // 4, so 4 is a satellite of 0 but not a mirror
+        let gb = undirected(5, &[(0, 1), (0, 2), (0, 3), (1, 4)]);
+        let gd = Graph::new(5);
+        assert!(mirrors(&gd, &gb, 0).is_empty());
+        assert_eq!(satellites(&gd, &gb, 0), vec![4]);
+        let branches = star_branch(&gd, &gb, 0);
+        assert_eq!(branches[0], Branch::new(vec![0], vec![]));
+        assert_eq!(branches[1], Branch::new(vec![1, 2, 3], vec![0, 4]));
+    }
+
+    #[test]
+    fn most_cycles_test_001() {
+        // two triangles sharing vertex 0
+        let mut gd = Graph::new(5);
+        gd.add_arc(0, 1);
+        gd.add_arc(1, 2);
+        gd.add_arc(2, 0);
+        gd.add_arc(0, 3);
+        gd.add_arc(3, 4);
+        gd.add_arc(4, 0);
+        let gb = Graph::new(5);
+        let branches = MostCycles {}.branch(&gd, &gb, &[]);
+        assert_eq!(branches, binary(0));
+    }
+
+    #[test]
+    fn orbital_test_001() {
+        // the two triangles are symmetric, so 1 and 3 as well as 2 and 4 form
+        // orbits, while 0 is fixed
+        let mut gd = Graph::new(5);
+        gd.add_arc(0, 1);
+        gd.add_arc(1, 2);
+        gd.add_arc(2, 0);
+        gd.add_arc(0, 3);
+        gd.add_arc(3, 4);
+        gd.add_arc(4, 0);
+        let gb = Graph::new(5);
+        let rule = rule(&Branching::MostCycles, true);
+        let branches = rule.branch_root(&gd, &gb, &[]);
+        assert_eq!(branches[0], Branch::new(vec![2], vec![]));
+        assert_eq!(branches[1], Branch::new(vec![], vec![2, 4]));
+        // below the root, the inner rule branches
+        assert_eq!(rule.branch(&gd, &gb, &[]), MostCycles {}.branch(&gd, &gb, &[]));
+    }
+}
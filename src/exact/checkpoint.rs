@@ -0,0 +1,244 @@
+//! Checkpoints of long exact runs. A checkpoint is a plain text file that
+//! starts with the vertices forced by the reductions and the graph left after
+//! them, followed by the name and the state of the algorithm that wrote it.
+//! Lists of vertices are written 1-indexed on a single line, like solutions,
+//! and graphs in the input format followed by the line of deleted vertices.
+use crate::{graph::Graph, io::Config};
+use std::{
+    fs,
+    io::{Error, ErrorKind, Result},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+/// Returns the error of a checkpoint file that cannot be read.
+pub fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, format!("damaged checkpoint: {}", message))
+}
+
+#[derive(Default)]
+pub struct Writer {
+    out: String,
+}
+
+impl Writer {
+    pub fn new() -> Writer {
+        Writer { out: String::new() }
+    }
+
+    /// Returns the lines written so far.
+    pub fn finish(self) -> String {
+        self.out
+    }
+
+    fn line(&mut self, line: &str) {
+        self.out.push_str(line);
+        self.out.push('\n');
+    }
+
+    pub fn numbers(&mut self, numbers: &[usize]) {
+        let line: Vec<_> = numbers.iter().map(|n| n.to_string()).collect();
+        self.line(&line.join(" "));
+    }
+
+    pub fn vertices(&mut self, vertices: &[u32]) {
+        let line: Vec<_> = vertices.iter().map(|v| (v + 1).to_string()).collect();
+        self.line(&line.join(" "));
+    }
+
+    pub fn graph(&mut self, graph: &Graph) {
+        self.out.push_str(&graph.to_string());
+        let active = graph.get_active_vertices();
+        let deleted: Vec<_> = (0..graph.total_vertices() as u32)
+            .filter(|v| active.binary_search(v).is_err())
+            .collect();
+        self.vertices(&deleted);
+    }
+}
+
+pub struct Reader {
+    lines: std::vec::IntoIter<String>,
+
+    /// The number of vertices of the reduced graph, once it has been read.
+    vertices: usize,
+}
+
+impl Reader {
+    pub fn new(content: &str) -> Reader {
+        let lines: Vec<_> = content.lines().map(String::from).collect();
+        Reader {
+            lines: lines.into_iter(),
+            vertices: usize::MAX,
+        }
+    }
+
+    fn line(&mut self) -> Result<String> {
+        self.lines.next().ok_or_else(|| invalid("the file ends early"))
+    }
+
+    /// Reads a line of exactly `count` numbers.
+    pub fn numbers(&mut self, count: usize) -> Result<Vec<usize>> {
+        let numbers = self
+            .line()?
+            .split_whitespace()
+            .map(|n| n.parse::<usize>().map_err(|_| invalid(&format!("{} is not a number", n))))
+            .collect::<Result<Vec<_>>>()?;
+        if numbers.len() != count {
+            return Err(invalid(&format!("expected {} numbers, found {}", count, numbers.len())));
+        }
+        Ok(numbers)
+    }
+
+    pub fn number(&mut self) -> Result<usize> {
+        Ok(self.numbers(1)?[0])
+    }
+
+    /// Reads a line of vertices, which are vertices of the reduced graph once
+    /// it has been read.
+    pub fn vertices(&mut self) -> Result<Vec<u32>> {
+        self.vertices_below(self.vertices)
+    }
+
+    fn vertices_below(&mut self, vertices: usize) -> Result<Vec<u32>> {
+        self.line()?
+            .split_whitespace()
+            .map(|v| match v.parse::<u32>() {
+                Ok(v) if v >= 1 && (v as usize) <= vertices => Ok(v - 1),
+                _ => Err(invalid(&format!("{} is not a vertex", v))),
+            })
+            .collect()
+    }
+
+    pub fn graph(&mut self) -> Result<Graph> {
+        // the header of the input format, of which only the vertices are used
+        let vertices = self.numbers(3)?[0];
+        let mut graph = Graph::new(vertices);
+        for v in 0..vertices {
+            let adj = self.vertices_below(vertices)?;
+            graph.set_adjacency(v as u32, adj);
+        }
+        for v in self.vertices_below(vertices)? {
+            graph.remove_vertex(v);
+        }
+        Ok(graph)
+    }
+}
+
+/// Writes the state of the running algorithm to the checkpoint file of the
+/// configuration, at most once per interval.
+pub struct Checkpointer {
+    path: Option<PathBuf>,
+    interval: Duration,
+    last: Instant,
+
+    /// The reduced graph and its forced vertices, which start every checkpoint.
+    prefix: String,
+
+    /// The remaining lines of the checkpoint that is resumed.
+    resumed: Option<Reader>,
+}
+
+impl Checkpointer {
+    pub fn new(config: &Config, forced: &[u32], graph: &Graph) -> Checkpointer {
+        let mut writer = Writer::new();
+        writer.vertices(forced);
+        writer.graph(graph);
+        Checkpointer {
+            path: config.checkpoint().clone(),
+            interval: Duration::from_secs(config.checkpoint_interval()),
+            last: Instant::now(),
+            prefix: writer.finish(),
+            resumed: None,
+        }
+    }
+
+    /// Reads the checkpoint file of the configuration, and returns the
+    /// vertices forced by the reductions, the reduced graph and a
+    /// checkpointer that provides the state of the algorithm.
+    pub fn resume(config: &Config) -> Result<(Vec<u32>, Graph, Checkpointer)> {
+        let path = config
+            .checkpoint()
+            .as_ref()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "--resume needs --checkpoint"))?;
+        let content = fs::read_to_string(path)?;
+        let mut reader = Reader::new(&content);
+        let forced = reader.vertices()?;
+        let graph = reader.graph()?;
+        reader.vertices = graph.total_vertices();
+        let mut checkpointer = Checkpointer::new(config, &forced, &graph);
+        checkpointer.resumed = Some(reader);
+        Ok((forced, graph, checkpointer))
+    }
+
+    /// Returns the state of the resumed checkpoint if it was written by the
+    /// algorithm `name`. The state is only handed out once.
+    pub fn take_state(&mut self, name: &str) -> Option<Reader> {
+        let mut reader = self.resumed.take()?;
+        match reader.lines.next() {
+            Some(line) if line == name => Some(reader),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the interval since the last checkpoint has passed.
+    pub fn due(&self) -> bool {
+        self.path.is_some() && self.last.elapsed() >= self.interval
+    }
+
+    /// Writes the checkpoint of the algorithm `name`, whose state is written
+    /// by `state`. The file is replaced atomically, so a run that dies while
+    /// writing keeps the previous checkpoint. A failed write is retried after
+    /// the next interval.
+    pub fn write(&mut self, name: &str, state: impl FnOnce(&mut Writer)) -> Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        self.last = Instant::now();
+        let mut writer = Writer {
+            out: self.prefix.clone(),
+        };
+        writer.line(name);
+        state(&mut writer);
+
+        let mut temporary = path.clone().into_os_string();
+        temporary.push(".tmp");
+        fs::write(&temporary, writer.out)?;
+        fs::rename(&temporary, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reader_test_001() {
+        // a graph with a deleted vertex survives a round trip
+        let mut graph = Graph::new(4);
+        graph.add_arc(0, 1);
+        graph.add_arc(1, 2);
+        graph.add_arc(2, 0);
+        graph.add_arc(3, 0);
+        graph.remove_vertex(3);
+
+        let mut writer = Writer::new();
+        writer.vertices(&[3, 0]);
+        writer.graph(&graph);
+        writer.numbers(&[7]);
+        let mut reader = Reader::new(&writer.finish());
+        assert_eq!(reader.vertices().unwrap(), vec![3, 0]);
+        assert_eq!(reader.graph().unwrap(), graph);
+        assert_eq!(reader.number().unwrap(), 7);
+    }
+
+    #[test]
+    fn reader_test_002() {
+        // damaged lines are errors instead of panics
+        let mut reader = Reader::new("1 x\n0\n2 3\n");
+        assert!(reader.vertices().is_err());
+        assert!(reader.vertices().is_err());
+        assert!(reader.number().is_err());
+        assert!(reader.number().is_err());
+    }
+}
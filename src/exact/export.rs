@@ -0,0 +1,400 @@
+//! Export of the model of [`super::grb_ilp`] to the CPLEX-LP and MPS formats,
+//! such that the instance can be handed to other MIP solvers, or as weighted
+//! MaxSAT instance in the WCNF format, and the verification of their
+//! solutions. Vertex *v* is the variable `v<v>` like in the Gurobi model, and
+//! the variable *v + 1* in WCNF. A solution of the model only covers the
+//! reduced graph, so the vertices forced by the reductions are written in a
+//! comment.
+use super::splitter::split_reduction;
+use crate::{
+    graph::{CliqueCover, Graph, HeuristicReduce, ThreeCliques},
+    io::Config,
+    util::Constraint,
+};
+use std::{fmt::Write as _, fs};
+
+/// Number of terms per line, LP readers limit the length of lines.
+const TERMS_PER_LINE: usize = 8;
+
+pub struct ModelFile {
+    /// The vertices forced before the model, which every solution contains.
+    forced: Vec<u32>,
+    rows: Vec<Constraint>,
+
+    /// A solution of the rows, written as MIP start.
+    start: Vec<u32>,
+
+    /// Whether the variables are continuous, which gives the LP relaxation.
+    relaxed: bool,
+
+    /// The arcs of the directed part, if acyclicity is encoded completely by
+    /// the transitive closure in WCNF.
+    arcs: Vec<(u32, u32)>,
+
+    /// Number of vertices of the graph, after which the auxiliary variables
+    /// of the WCNF are numbered.
+    vertices: usize,
+}
+
+impl ModelFile {
+    /// Builds the model of the reduced `graph` from the constraints of the
+    /// split reduction and the clique rows of its undirected part.
+    pub fn new(mut graph: Graph, config: &Config) -> ModelFile {
+        let vertices = graph.total_vertices();
+        let mut forced = if config.reduce() {
+            graph.reduce()
+        } else {
+            Vec::new()
+        };
+        if graph.vertices() == 0 {
+            return ModelFile {
+                forced,
+                rows: Vec::new(),
+                start: Vec::new(),
+                relaxed: config.relaxed(),
+                arcs: Vec::new(),
+                vertices,
+            };
+        }
+        let data = split_reduction(graph, config);
+        let mut arcs = Vec::new();
+        if config.closure() {
+            for u in data.directed_graph.get_active_vertices() {
+                for v in data.directed_graph.get_outgoing(&u) {
+                    arcs.push((u, *v));
+                }
+            }
+        }
+        forced.extend_from_slice(&data.split_reduced);
+        forced.sort_unstable();
+
+        let mut rows = data.constraints;
+        for (a, b, c) in data.undirected_graph.undirected_three_cliques() {
+            rows.push(Constraint::new(vec![a, b, c], 2));
+        }
+        for clique in data.undirected_graph.clique_cover() {
+            if clique.len() > 3 {
+                let lower_bound = clique.len() as u32 - 1;
+                rows.push(Constraint::new(clique, lower_bound));
+            }
+        }
+
+        ModelFile {
+            forced,
+            rows,
+            start: data.upper_bound,
+            relaxed: config.relaxed(),
+            arcs,
+            vertices,
+        }
+    }
+
+    /// Returns the sorted vertices that occur in a row or an arc.
+    fn variables(&self) -> Vec<u32> {
+        let mut variables: Vec<_> = self.rows.iter().flat_map(|row| row.variables().to_vec()).collect();
+        for (u, v) in &self.arcs {
+            variables.push(*u);
+            variables.push(*v);
+        }
+        variables.sort_unstable();
+        variables.dedup();
+        variables
+    }
+
+    fn forced_comment(&self) -> String {
+        let names: Vec<_> = self.forced.iter().map(|v| name(*v)).collect();
+        format!("forced: {}", names.join(" "))
+    }
+
+    pub fn lp(&self) -> String {
+        let variables = self.variables();
+        let mut out = String::new();
+        writeln!(out, "\\ DFVS model written by hex").unwrap();
+        writeln!(out, "\\ {}", self.forced_comment()).unwrap();
+        writeln!(out, "Minimize").unwrap();
+        write_sum(&mut out, " obj:", &variables);
+        writeln!(out).unwrap();
+
+        writeln!(out, "Subject To").unwrap();
+        for (i, row) in self.rows.iter().enumerate() {
+            write_sum(&mut out, &format!(" c{}:", i), row.variables());
+            writeln!(out, " >= {}", row.lower_bound()).unwrap();
+        }
+
+        if self.relaxed {
+            writeln!(out, "Bounds").unwrap();
+            for v in &variables {
+                writeln!(out, " 0 <= {} <= 1", name(*v)).unwrap();
+            }
+        } else {
+            writeln!(out, "Binaries").unwrap();
+            for chunk in variables.chunks(TERMS_PER_LINE) {
+                let names: Vec<_> = chunk.iter().map(|v| name(*v)).collect();
+                writeln!(out, " {}", names.join(" ")).unwrap();
+            }
+        }
+        writeln!(out, "End").unwrap();
+        out
+    }
+
+    pub fn mps(&self) -> String {
+        let variables = self.variables();
+        let mut columns = vec![Vec::new(); variables.len()];
+        for (i, row) in self.rows.iter().enumerate() {
+            for v in row.variables() {
+                columns[variables.binary_search(v).unwrap()].push(i);
+            }
+        }
+
+        let mut out = String::new();
+        writeln!(out, "* DFVS model written by hex").unwrap();
+        writeln!(out, "* {}", self.forced_comment()).unwrap();
+        writeln!(out, "NAME hex").unwrap();
+        writeln!(out, "ROWS").unwrap();
+        writeln!(out, " N obj").unwrap();
+        for i in 0..self.rows.len() {
+            writeln!(out, " G c{}", i).unwrap();
+        }
+
+        writeln!(out, "COLUMNS").unwrap();
+        if !self.relaxed {
+            writeln!(out, " MARKER 'MARKER' 'INTORG'").unwrap();
+        }
+        for (v, rows) in variables.iter().zip(&columns) {
+            writeln!(out, " {} obj 1", name(*v)).unwrap();
+            for i in rows {
+                writeln!(out, " {} c{} 1", name(*v), i).unwrap();
+            }
+        }
+        if !self.relaxed {
+            writeln!(out, " MARKER 'MARKER' 'INTEND'").unwrap();
+        }
+
+        writeln!(out, "RHS").unwrap();
+        for (i, row) in self.rows.iter().enumerate() {
+            writeln!(out, " rhs c{} {}", i, row.lower_bound()).unwrap();
+        }
+        writeln!(out, "BOUNDS").unwrap();
+        for v in &variables {
+            writeln!(out, " UP bnd {} 1", name(*v)).unwrap();
+        }
+        writeln!(out, "ENDATA").unwrap();
+        out
+    }
+
+    /// Returns the weighted MaxSAT instance in the WCNF format of the MaxSAT
+    /// evaluations since 2022. Rows with a lower bound of 1 are hard clauses
+    /// and every vertex has a soft unit clause to be kept. The other rows are
+    /// implied by the clauses of the undirected edges. Without arcs, only the
+    /// cycles found so far are encoded, so an optimum may leave cycles.
+    pub fn wcnf(&self) -> String {
+        let variables = self.variables();
+        let mut out = String::new();
+        writeln!(out, "c DFVS model written by hex").unwrap();
+        writeln!(out, "c {}", self.forced_comment()).unwrap();
+        for row in self.rows.iter().filter(|row| row.lower_bound() == 1) {
+            let literals: Vec<_> = row.variables().iter().map(|v| (v + 1).to_string()).collect();
+            writeln!(out, "h {} 0", literals.join(" ")).unwrap();
+        }
+
+        // reachable[u][v] is true if a path of kept vertices leads from u to
+        // v, and no vertex may reach itself
+        let position = |v: u32| variables.binary_search(&v).unwrap();
+        let reachable = |u: u32, v: u32| self.vertices + 1 + position(u) * variables.len() + position(v);
+        for (u, v) in &self.arcs {
+            writeln!(out, "h {} {} {} 0", u + 1, v + 1, reachable(*u, *v)).unwrap();
+        }
+        if !self.arcs.is_empty() {
+            for u in &variables {
+                for (v, w) in &self.arcs {
+                    writeln!(out, "h -{} {} {} 0", reachable(*u, *v), w + 1, reachable(*u, *w)).unwrap();
+                }
+                writeln!(out, "h -{} 0", reachable(*u, *u)).unwrap();
+            }
+        }
+
+        for v in &variables {
+            writeln!(out, "1 -{} 0", v + 1).unwrap();
+        }
+        out
+    }
+
+    /// Returns the MIP start in the format of MST files.
+    pub fn start(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "# MIP start written by hex").unwrap();
+        for v in self.variables() {
+            let value = if self.start.contains(&v) { 1 } else { 0 };
+            writeln!(out, "{} {}", name(v), value).unwrap();
+        }
+        out
+    }
+}
+
+/// Writes `prefix` followed by the sum of the variables of `vertices`.
+fn write_sum(out: &mut String, prefix: &str, vertices: &[u32]) {
+    out.push_str(prefix);
+    for (i, v) in vertices.iter().enumerate() {
+        if i > 0 {
+            out.push_str(" +");
+            if i % TERMS_PER_LINE == 0 {
+                out.push_str("\n  ");
+            }
+        }
+        write!(out, " {}", name(*v)).unwrap();
+    }
+}
+
+fn name(vertex: u32) -> String {
+    format!("v{}", vertex)
+}
+
+fn vertex(name: &str) -> Option<u32> {
+    name.strip_prefix('v')?.parse().ok()
+}
+
+/// Returns the vertices forced before the model from the comment of an
+/// exported model file.
+fn read_forced(model: &str) -> Vec<u32> {
+    model
+        .lines()
+        .find_map(|line| line.split_once("forced:"))
+        .map(|(_, names)| names.split_whitespace().filter_map(vertex).collect())
+        .unwrap_or_default()
+}
+
+/// Returns the vertices whose variable is 1 in a solution file. Solvers
+/// write a variable and its value on one line, possibly among other
+/// columns, so the value is taken from the token after the name.
+fn read_solution(solution: &str) -> Vec<u32> {
+    let mut vertices = Vec::new();
+    for line in solution.lines() {
+        if line.starts_with('#') {
+            continue;
+        }
+        let tokens: Vec<_> = line.split_whitespace().collect();
+        for i in 0..tokens.len().saturating_sub(1) {
+            if let (Some(v), Ok(value)) = (vertex(tokens[i]), tokens[i + 1].parse::<f64>()) {
+                if value >= 0.5 {
+                    vertices.push(v);
+                }
+                break;
+            }
+        }
+    }
+    vertices
+}
+
+/// Returns the vertices whose variable is true in the model of a MaxSAT
+/// solver, given as `v` line of literals or of the values of all variables.
+/// The auxiliary variables after the `vertices` variables are dropped.
+fn read_model(model: &str, vertices: usize) -> Vec<u32> {
+    let mut true_variables = Vec::new();
+    for line in model.lines() {
+        let values = match line.strip_prefix("v ") {
+            Some(values) => values.trim(),
+            None => continue,
+        };
+        if values.chars().all(|c| c == '0' || c == '1') {
+            for (i, value) in values.chars().enumerate() {
+                if value == '1' {
+                    true_variables.push(i + 1);
+                }
+            }
+        } else {
+            for literal in values.split_whitespace() {
+                if let Ok(variable) = literal.parse::<usize>() {
+                    true_variables.push(variable);
+                }
+            }
+        }
+    }
+    true_variables
+        .into_iter()
+        .filter(|variable| (1..=vertices).contains(variable))
+        .map(|variable| variable as u32 - 1)
+        .collect()
+}
+
+/// Writes the model of `graph` to the export file of the configuration, as
+/// MPS if its extension is `mps`, as WCNF if it is `wcnf` and as CPLEX-LP
+/// otherwise, and the MIP start next to it with the extension `mst`.
+pub fn export(graph: Graph, config: &Config) {
+    let path = config.export().as_ref().unwrap();
+    let model = ModelFile::new(graph, config);
+    let text = match path.extension() {
+        Some(extension) if extension == "mps" => model.mps(),
+        Some(extension) if extension == "wcnf" => model.wcnf(),
+        _ => model.lp(),
+    };
+    fs::write(path, text).unwrap();
+    fs::write(path.with_extension("mst"), model.start()).unwrap();
+}
+
+/// Reads the solution of the exported model given in the configuration, and
+/// returns it together with the forced vertices if it is a DFVS of `graph`.
+pub fn verify(graph: &Graph, config: &Config) -> Option<Vec<u32>> {
+    let model = config.export().as_ref().unwrap();
+    let solution = config.verify().as_ref().unwrap();
+    let mut dfvs = read_forced(&fs::read_to_string(model).unwrap());
+    let solution = fs::read_to_string(solution).unwrap();
+    match model.extension() {
+        Some(extension) if extension == "wcnf" => {
+            dfvs.append(&mut read_model(&solution, graph.total_vertices()))
+        }
+        _ => dfvs.append(&mut read_solution(&solution)),
+    }
+    dfvs.sort_unstable();
+    dfvs.dedup();
+    graph.is_acyclic_with_fvs(&dfvs).then_some(dfvs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn model_file_test_001() {
+        let model = ModelFile {
+            forced: vec![4],
+            rows: vec![Constraint::new(vec![0, 1], 1), Constraint::new(vec![1, 2, 3], 2)],
+            start: vec![1, 2],
+            relaxed: false,
+            arcs: Vec::new(),
+            vertices: 5,
+        };
+        let lp = model.lp();
+        assert!(lp.contains(" obj: v0 + v1 + v2 + v3\n"));
+        assert!(lp.contains(" c1: v1 + v2 + v3 >= 2\n"));
+        assert!(lp.contains("Binaries\n v0 v1 v2 v3\n"));
+        assert_eq!(read_forced(&lp), vec![4]);
+
+        let mps = model.mps();
+        assert!(mps.contains(" v1 obj 1\n v1 c0 1\n v1 c1 1\n"));
+        assert!(mps.contains(" rhs c1 2\n"));
+        assert_eq!(read_forced(&mps), vec![4]);
+        assert_eq!(read_solution(&model.start()), vec![1, 2]);
+
+        let wcnf = model.wcnf();
+        assert!(wcnf.contains("h 1 2 0\n"));
+        assert!(!wcnf.contains("h 2 3 4 0\n"));
+        assert!(wcnf.contains("1 -4 0\n"));
+        assert_eq!(read_forced(&wcnf), vec![4]);
+    }
+
+    #[test]
+    fn read_model_test_001() {
+        // variable 7 is auxiliary
+        assert_eq!(read_model("o 2\ns OPTIMUM FOUND\nv -1 2 -3 4 -5 7\n", 5), vec![1, 3]);
+        assert_eq!(read_model("s OPTIMUM FOUND\nv 0101001\n", 5), vec![1, 3]);
+    }
+
+    #[test]
+    fn read_solution_test_001() {
+        // a Gurobi solution file and a column-wise report of another solver
+        let gurobi = "# Objective value = 2\nv0 1\nv1 0\nv3 1\n";
+        assert_eq!(read_solution(gurobi), vec![0, 3]);
+        let columns = "Optimal - objective value 1\n      0 v0  0  1\n      1 v2  1  1\n";
+        assert_eq!(read_solution(columns), vec![2]);
+    }
+}
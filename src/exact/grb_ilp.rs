@@ -1,17 +1,33 @@
 use std::path::PathBuf;
 
-use super::{recover_solution, splitter::split_reduction};
+use super::{
+    checkpoint::{Checkpointer, Reader, Writer},
+    pool::ConstraintPool,
+    recover_solution,
+    splitter::{split_reduction, ILPData},
+};
 use crate::{
     exact::vc_solver,
-    graph::{EdgeCycleCover, Graph, ThreeCliques, Undirected},
-    heur::hitting_set_upper_bound,
+    graph::{CliqueCover, EdgeCycleCover, Graph, Symmetry, ThreeCliques, Undirected, WeightedCycles},
+    heur::{extractor, hitting_set_upper_bound, HittingSetOracle, ImplicitHittingSet},
     io::Config,
     util::Constraint,
 };
-use grb::{expr::LinExpr, prelude::*};
+use grb::{
+    callback::{Callback, CbResult, Where},
+    expr::LinExpr,
+    prelude::*,
+};
 use rustc_hash::FxHashSet;
 
-pub fn solve(graph: Graph, config: &Config) -> Vec<u32> {
+/// Name of the state of this algorithm in checkpoints.
+const STATE: &str = "ilp";
+
+/// Number of consecutive solves in which a cycle row may have slack before it
+/// is removed from the model.
+const MAX_AGE: usize = 5;
+
+pub fn solve(graph: Graph, config: &Config, checkpointer: &mut Checkpointer) -> Vec<u32> {
     let _out = shh::stdout();
     let vertices = graph.total_vertices();
 
@@ -24,11 +40,11 @@ pub fn solve(graph: Graph, config: &Config) -> Vec<u32> {
             try_vc_solver = false;
         }
     }
-    let data = split_reduction(graph, &config);
+    let data = resume_or_reduce(graph, config, checkpointer);
     let graph = data.directed_graph;
     let undirected_graph = data.undirected_graph;
-    let mut constraints = data.constraints;
-    let mut upper_bound = data.upper_bound;
+    let constraints = data.constraints;
+    let upper_bound = data.upper_bound;
     let mut split_reduced = data.split_reduced;
 
     if graph.is_empty() && try_vc_solver {
@@ -50,9 +66,236 @@ pub fn solve(graph: Graph, config: &Config) -> Vec<u32> {
         return dfvs;
     }
     
+    let (mut model, vars, delta) = build_model(vertices, &constraints, &undirected_graph, &upper_bound);
+    if config.symmetry() {
+        add_symmetry_rows(&mut model, &vars, &graph, &undirected_graph);
+    }
+    let oracle = GurobiOracle {
+        model,
+        vars,
+        rows: constraints.clone(),
+        pool: ConstraintPool::new(MAX_AGE),
+        vertices,
+        solved: false,
+    };
+
+    // Our upper bound is returned once an optimal solution of the ILP has its
+    // size, since the ILP may shift variables and break our solution.
+    let mut ihs = ImplicitHittingSet::with_added(oracle, extractor(config.cores()), constraints, vertices)
+        .with_upper_bound(upper_bound.clone());
+    let mut dfvs = ihs.solve(&graph, |constraints| {
+        if checkpointer.due() {
+            let result = checkpointer.write(STATE, |writer| {
+                write_state(writer, &split_reduced, constraints, &upper_bound, &graph, &undirected_graph)
+            });
+            if let Err(err) = result {
+                eprintln!("failed to write checkpoint: {}", err);
+            }
+        }
+    });
+    dfvs.append(&mut split_reduced);
+    eprintln!("{}", ihs.oracle().rows.len() + ihs.oracle().pool.len() + delta);
+    eprintln!("{}", alive_variables(ihs.constraints()));
+    if ihs.iterations() == 1 {
+        eprintln!("iters = 1");
+    } else {
+        eprintln!("{}", ihs.iterations());
+    }
+    dfvs
+}
+
+/// Hitting set oracle on the ILP, whose cycle rows age in a pool.
+struct GurobiOracle {
+    model: Model,
+    vars: Vec<Var>,
+
+    /// The rows the model was built with, which stay in the model.
+    rows: Vec<Constraint>,
+    pool: ConstraintPool,
+    vertices: usize,
+
+    /// Whether the model has been solved, after which the start is taken from
+    /// the hitting set heuristic instead of the upper bound of the graph.
+    solved: bool,
+}
+
+impl HittingSetOracle for GurobiOracle {
+    fn add(&mut self, constraints: &[Constraint]) {
+        for constraint in constraints {
+            self.pool.add(&mut self.model, &self.vars, constraint.clone());
+        }
+    }
+
+    /// Rows removed from the model are not all cycles of the directed graph,
+    /// so a solution is only returned once none of them had to be restored.
+    fn hit(&mut self) -> Vec<u32> {
+        let mut dfvs = Vec::new();
+        loop {
+            if self.solved {
+                // this is an upper bound on the hitting set instance, not an
+                // upper for the graph itself
+                let mut rows = self.rows.clone();
+                rows.extend_from_slice(self.pool.active());
+                let upper_bound = hitting_set_upper_bound(&rows, self.vertices);
+                self.model.reset().unwrap();
+                for variable in &upper_bound {
+                    self.model
+                        .set_obj_attr(attr::Start, &self.vars[*variable as usize], 1.)
+                        .unwrap();
+                }
+            }
+
+            let _out = shh::stdout();
+            self.model.optimize().unwrap();
+            self.solved = true;
+            recover_solution(&self.model, &self.vars, &mut dfvs);
+            self.pool.age(&mut self.model, &dfvs, self.vertices);
+            if self.pool.restore(&mut self.model, &self.vars, &dfvs) == 0 {
+                return dfvs;
+            }
+        }
+    }
+
+    fn is_exact(&self) -> bool {
+        true
+    }
+}
+
+/// Separates cycle constraints during a single branch-and-cut search: cycles
+/// missed by a new incumbent are added as lazy constraints, and cycles that
+/// are too light under the relaxation of a node are added as cuts.
+struct CycleSeparator<'a> {
+    graph: &'a Graph,
+    undirected_graph: &'a Graph,
+    vars: &'a [Var],
+    split_reduced: &'a [u32],
+    upper_bound: &'a [u32],
+
+    /// The constraints of the model, including the separated cycles.
+    constraints: Vec<Constraint>,
+    checkpointer: &'a mut Checkpointer,
+}
+
+impl CycleSeparator<'_> {
+    fn expr(&self, cycle: &[u32]) -> LinExpr {
+        let mut expr = LinExpr::new();
+        for v in cycle {
+            expr.add_term(1., self.vars[*v as usize]);
+        }
+        expr
+    }
+}
+
+impl Callback for CycleSeparator<'_> {
+    fn callback(&mut self, w: Where) -> CbResult {
+        match w {
+            Where::MIPSol(ctx) => {
+                let values = ctx.get_solution(self.vars)?;
+                let dfvs: Vec<u32> = (0..values.len() as u32)
+                    .filter(|v| values[*v as usize] >= 0.5)
+                    .collect();
+                for cycle in self.graph.disjoint_edge_cycle_cover(&dfvs) {
+                    let expr = self.expr(&cycle);
+                    ctx.add_lazy(c!(expr >= 1))?;
+                    self.constraints.push(Constraint::new(cycle, 1));
+                }
+
+                if self.checkpointer.due() {
+                    let (constraints, graph) = (&self.constraints, self.graph);
+                    let (split_reduced, upper_bound) = (self.split_reduced, self.upper_bound);
+                    let undirected_graph = self.undirected_graph;
+                    let result = self.checkpointer.write(STATE, |writer| {
+                        write_state(writer, split_reduced, constraints, upper_bound, graph, undirected_graph)
+                    });
+                    if let Err(err) = result {
+                        eprintln!("failed to write checkpoint: {}", err);
+                    }
+                }
+            }
+            Where::MIPNode(ctx) => {
+                if ctx.status()? != Status::Optimal {
+                    return Ok(());
+                }
+                let values = ctx.get_solution(self.vars)?;
+                for cycle in self.graph.light_cycles(&values, 1. - CUT_TOLERANCE) {
+                    let expr = self.expr(&cycle);
+                    ctx.add_cut(c!(expr >= 1))?;
+                    self.constraints.push(Constraint::new(cycle, 1));
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Cycles are only separated at fractional nodes if they are violated by more
+/// than this.
+const CUT_TOLERANCE: f64 = 1e-3;
+
+/// Branch-and-cut variant of [`solve`], which keeps a single search tree and
+/// separates the cycle constraints in a callback instead of re-solving the
+/// model whenever its solution contains a cycle.
+pub fn branch_and_cut(graph: Graph, config: &Config, checkpointer: &mut Checkpointer) -> Vec<u32> {
+    let _out = shh::stdout();
+    let vertices = graph.total_vertices();
+    let data = resume_or_reduce(graph, config, checkpointer);
+
+    let (mut model, vars, delta) = build_model(
+        vertices,
+        &data.constraints,
+        &data.undirected_graph,
+        &data.upper_bound,
+    );
+    if config.symmetry() {
+        add_symmetry_rows(&mut model, &vars, &data.directed_graph, &data.undirected_graph);
+    }
+    model.set_param(param::LazyConstraints, 1).unwrap();
+    // cuts refer to the original variables, so presolve has to keep them
+    model.set_param(param::PreCrush, 1).unwrap();
+
+    let mut separator = CycleSeparator {
+        graph: &data.directed_graph,
+        undirected_graph: &data.undirected_graph,
+        vars: &vars,
+        split_reduced: &data.split_reduced,
+        upper_bound: &data.upper_bound,
+        constraints: data.constraints.clone(),
+        checkpointer,
+    };
+    model.optimize_with_callback(&mut separator).unwrap();
+
+    let mut dfvs = Vec::new();
+    recover_solution(&model, &vars, &mut dfvs);
+    eprintln!("{}", separator.constraints.len() + delta);
+    eprintln!("{}", alive_variables(&separator.constraints));
+    dfvs.extend_from_slice(&data.split_reduced);
+    dfvs
+}
+
+fn alive_variables(constraints: &[Constraint]) -> usize {
+    let mut set = FxHashSet::default();
+    for constraint in constraints {
+        for var in constraint.variables() {
+            set.insert(*var);
+        }
+    }
+    set.len()
+}
+
+/// Builds the model over the constraints of the split reduction and the
+/// triangles and clique cover of the undirected graph, starting from
+/// `upper_bound`. Returns the model, its variables and the number of clique
+/// constraints.
+pub(super) fn build_model(
+    vertices: usize,
+    constraints: &[Constraint],
+    undirected_graph: &Graph,
+    upper_bound: &[u32],
+) -> (Model, Vec<Var>, usize) {
     let mut model = super::init_model();
     // model.set_obj_sense(Sense::Minimize);
-    
+
     let mut vars = Vec::with_capacity(vertices);
     for i in 0..vertices {
         let n = format!("v{}", i);
@@ -63,7 +306,7 @@ pub fn solve(graph: Graph, config: &Config) -> Vec<u32> {
         .set_objective(vars.iter().sum::<Expr>(), Minimize)
         .unwrap();
 
-    for constraint in &constraints {
+    for constraint in constraints {
         let mut expr = LinExpr::new();
         for v in constraint.variables() {
             let var = vars[*v as usize];
@@ -83,81 +326,107 @@ pub fn solve(graph: Graph, config: &Config) -> Vec<u32> {
         model.add_constr("", c!(va + vb + vc >= 2)).unwrap();
     }
 
-    // let upper = hitting_set_upper_bound(&preprocess_constraints, graph.total_vertices());
-    // model.remove_initial_solution();
-    for variable in &upper_bound {
+    // the triangles are already covered, only larger cliques are added
+    let cliques: Vec<_> = undirected_graph
+        .clique_cover()
+        .into_iter()
+        .filter(|clique| clique.len() > 3)
+        .collect();
+    let delta = delta + cliques.len();
+    for clique in cliques {
+        let lb = clique.len() - 1;
+        let mut expr = LinExpr::new();
+        for v in clique {
+            expr.add_term(1., vars[v as usize]);
+        }
+        model.add_constr("", c!(expr >= lb)).unwrap();
+    }
+
+    for variable in upper_bound {
         model
             .set_obj_attr(attr::Start, &vars[*variable as usize], 1.)
             .unwrap();
     }
 
-    model.optimize().unwrap();
-    recover_solution(&model, &vars, &mut dfvs);
+    (model, vars, delta)
+}
 
-    // Our upper bound is an optimal solution for the ILP, which is
-    // and it is also a DFVS, so it must be an optimal DFVS
-    // We perform this check since the ILP may shift variables, breaking our
-    // solution.
-    if dfvs.len() == upper_bound.len() {
-        upper_bound.append(&mut split_reduced);
-        eprintln!("{}", constraints.len() + delta);
-        eprintln!("{}", alive_variables(&constraints));
-        eprintln!("iters = 1");
-        return upper_bound;
-    }
+/// Adds orbital fixing rows for the largest orbit *O* of the split graph. If
+/// some optimal solution meets *O*, an automorphism maps it to an optimal
+/// solution that contains the first vertex *r* of *O*, so *x_r >= x_v* for all
+/// *v* in *O*. Only one orbit is fixed, since the automorphism used for a
+/// second orbit may undo the first.
+pub(super) fn add_symmetry_rows(model: &mut Model, vars: &[Var], directed_graph: &Graph, undirected_graph: &Graph) {
+    let graph = directed_graph.clone() + undirected_graph.clone();
+    // vertices removed by the split reduction form an orbit without arcs
+    let orbit = graph
+        .orbits()
+        .into_iter()
+        .filter(|orbit| !graph.get_outgoing(&orbit[0]).is_empty())
+        .max_by_key(|orbit| orbit.len());
+    let orbit = match orbit {
+        Some(orbit) => orbit,
+        None => return,
+    };
 
-    // Even though our upper bound is not optimal, we have found an optimal
-    // DFVS
-    if graph.is_acyclic_with_fvs(&dfvs) {
-        dfvs.append(&mut split_reduced);
-        eprintln!("{}", constraints.len() + delta);
-        eprintln!("{}", alive_variables(&constraints));
-        eprintln!("iters = 1");
-        return dfvs;
+    let first = vars[orbit[0] as usize];
+    for v in &orbit[1..] {
+        let var = vars[*v as usize];
+        model.add_constr("", c!(first - var >= 0)).unwrap();
     }
+}
 
-    // We unfortunately do not have a solution, we need to iteratively
-    // add edge cycle covers until we do
-    let mut iters = 1;
-    while !graph.is_acyclic_with_fvs(&dfvs) {
-        iters += 1;
-        let cycles = graph.disjoint_edge_cycle_cover(&dfvs);
-        for cycle in cycles {
-            let mut expr = LinExpr::new();
-            for v in &cycle {
-                let var = vars[*v as usize];
-                expr.add_term(1., var);
-            }
-            model.add_constr("", c!(expr >= 1)).unwrap();
-            constraints.push(Constraint::new(cycle, 1));
-        }
-        // this is an upper bound on the hitting set instance, not an upper
-        // for the graph itself
-        let upper_bound = hitting_set_upper_bound(&constraints, vertices);
-        model.reset().unwrap();
-        for variable in &upper_bound {
-            model
-                .set_obj_attr(attr::Start, &vars[*variable as usize], 1.)
-                .unwrap();
-        }
+/// Writes the result of the split reduction together with the constraints
+/// found so far, from which the model is rebuilt on resumption.
+fn write_state(
+    writer: &mut Writer,
+    split_reduced: &[u32],
+    constraints: &[Constraint],
+    upper_bound: &[u32],
+    directed_graph: &Graph,
+    undirected_graph: &Graph,
+) {
+    writer.vertices(split_reduced);
+    writer.vertices(upper_bound);
+    writer.graph(directed_graph);
+    writer.graph(undirected_graph);
+    writer.numbers(&[constraints.len()]);
+    for constraint in constraints {
+        writer.numbers(&[constraint.lower_bound() as usize]);
+        writer.vertices(constraint.variables());
+    }
+}
 
-        let _out = shh::stdout();
-        model.optimize().unwrap();
-        recover_solution(&model, &vars, &mut dfvs);
+fn read_state(reader: &mut Reader) -> std::io::Result<ILPData> {
+    let split_reduced = reader.vertices()?;
+    let upper_bound = reader.vertices()?;
+    let directed_graph = reader.graph()?;
+    let undirected_graph = reader.graph()?;
+    let count = reader.number()?;
+    let mut constraints = Vec::with_capacity(count);
+    for _ in 0..count {
+        let lower_bound = reader.number()? as u32;
+        constraints.push(Constraint::new(reader.vertices()?, lower_bound));
     }
-    dfvs.append(&mut split_reduced);
-    eprintln!("{}", constraints.len() + delta);
-    eprintln!("{}", alive_variables(&constraints));
-    eprintln!("{iters}");
-    dfvs
+    Ok(ILPData {
+        split_reduced,
+        constraints,
+        upper_bound,
+        lower_bound: 0,
+        directed_graph,
+        undirected_graph,
+    })
 }
 
-fn alive_variables(constraints: &[Constraint]) -> usize {
-    let mut set = FxHashSet::default();
-    for constraint in constraints {
-        for var in constraint.variables() {
-            set.insert(*var);
+/// Returns the state of the resumed checkpoint, or the split reduction of
+/// `graph` if there is none or it is damaged.
+fn resume_or_reduce(graph: Graph, config: &Config, checkpointer: &mut Checkpointer) -> ILPData {
+    match checkpointer.take_state(STATE).map(|mut state| read_state(&mut state)) {
+        Some(Ok(data)) => data,
+        Some(Err(err)) => {
+            eprintln!("{}, solving from the start", err);
+            split_reduction(graph, config)
         }
+        None => split_reduction(graph, config),
     }
-    set.len()
-}
\ No newline at end of file
+}
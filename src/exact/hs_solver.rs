@@ -0,0 +1,109 @@
+//! Exact solver for plain hitting set instances, such as those of PACE 2025.
+//! The instance is reduced and split into components whose sets share no
+//! element. Every component is solved by the branch and bound for hitting
+//! sets, unless the packing lower bound already meets the simulated annealing
+//! upper bound.
+use crate::{
+    heur::hitting_set_upper_bound_custom,
+    util::{minimum_hitting_set, packing_lower_bound, reduce_hitting_set, Constraint},
+};
+
+/// Number of steps of the simulated annealing per set of a component, up to
+/// the steps of [`crate::heur::hitting_set_upper_bound`].
+const STEPS_PER_SET: usize = 1000;
+const MAX_STEPS: usize = 1_000_000;
+
+pub fn solve_hitting_set(constraints: Vec<Constraint>, elements: usize) -> Vec<u32> {
+    let mut sets: Vec<_> = constraints
+        .into_iter()
+        .map(|constraint| {
+            debug_assert_eq!(constraint.lower_bound(), 1);
+            constraint.variables().to_vec()
+        })
+        .collect();
+    let reduction = reduce_hitting_set(&mut sets, elements as u32);
+    let mut solution = reduction.forced;
+
+    let components = components(reduction.reduced, elements);
+    for component in components {
+        // the elements of the component are renamed to 0, 1, ... such that
+        // the solvers only allocate for the component
+        let mut local: Vec<_> = component.iter().flatten().copied().collect();
+        local.sort_unstable();
+        local.dedup();
+        let constraints: Vec<_> = component
+            .iter()
+            .map(|set| {
+                let variables = set.iter().map(|e| local.binary_search(e).unwrap() as u32).collect();
+                Constraint::new(variables, 1)
+            })
+            .collect();
+
+        let steps = (STEPS_PER_SET * constraints.len()).min(MAX_STEPS);
+        let heuristic = hitting_set_upper_bound_custom(&constraints, local.len(), steps as i32);
+        let packing = packing_lower_bound(&constraints, local.len());
+        let hitting_set = if packing == heuristic.len() {
+            heuristic
+        } else {
+            minimum_hitting_set(&constraints, local.len(), heuristic)
+        };
+        solution.extend(hitting_set.into_iter().map(|v| local[v as usize]));
+    }
+
+    solution.sort_unstable();
+    solution
+}
+
+/// Returns the sets grouped by the components of the elements they share.
+fn components(sets: Vec<Vec<u32>>, elements: usize) -> Vec<Vec<Vec<u32>>> {
+    let mut parent: Vec<u32> = (0..elements as u32).collect();
+    for set in &sets {
+        for element in &set[1..] {
+            let (a, b) = (find(&mut parent, set[0]), find(&mut parent, *element));
+            parent[a as usize] = b;
+        }
+    }
+
+    let mut components = vec![Vec::new(); elements];
+    for set in sets {
+        let root = find(&mut parent, set[0]);
+        components[root as usize].push(set);
+    }
+    components.into_iter().filter(|component| !component.is_empty()).collect()
+}
+
+fn find(parent: &mut [u32], mut element: u32) -> u32 {
+    while parent[element as usize] != element {
+        parent[element as usize] = parent[parent[element as usize] as usize];
+        element = parent[element as usize];
+    }
+    element
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{components, solve_hitting_set};
+    use crate::util::Constraint;
+
+    #[test]
+    fn components_test_001() {
+        let sets = vec![vec![0, 1], vec![2, 3], vec![1, 4], vec![5]];
+        let components = components(sets, 6);
+        assert_eq!(components.len(), 3);
+        assert!(components.contains(&vec![vec![0, 1], vec![1, 4]]));
+    }
+
+    #[test]
+    fn solve_hitting_set_test_001() {
+        // a 5-cycle of pairs needs 3 elements, a disjoint 4-cycle needs 2
+        let mut constraints: Vec<_> = (0..5).map(|i| Constraint::new(vec![i, (i + 1) % 5], 1)).collect();
+        for i in 0..4 {
+            constraints.push(Constraint::new(vec![5 + i, 5 + (i + 1) % 4], 1));
+        }
+        let solution = solve_hitting_set(constraints.clone(), 9);
+        assert_eq!(solution.len(), 5);
+        assert!(constraints
+            .iter()
+            .all(|constraint| constraint.variables().iter().any(|v| solution.contains(v))));
+    }
+}
@@ -0,0 +1,23 @@
+//! Implicit hitting set algorithm with the branch and bound for hitting sets
+//! as oracle, which needs no MIP solver.
+use super::splitter::split_reduction;
+use crate::{
+    graph::Graph,
+    heur::{extractor, BranchAndBoundOracle, ImplicitHittingSet},
+    io::Config,
+};
+
+pub fn solve(graph: Graph, config: &Config) -> Vec<u32> {
+    let vertices = graph.total_vertices();
+    let data = split_reduction(graph, config);
+
+    let oracle = BranchAndBoundOracle::new(vertices);
+    let mut ihs = ImplicitHittingSet::new(oracle, extractor(config.cores()), data.constraints, vertices)
+        .with_upper_bound(data.upper_bound);
+    let mut dfvs = ihs.solve(&data.directed_graph, |_| {});
+
+    eprintln!("{}", ihs.constraints().len());
+    eprintln!("iters = {}", ihs.iterations());
+    dfvs.extend_from_slice(&data.split_reduced);
+    dfvs
+}
@@ -1,13 +1,22 @@
 use crate::{
     graph::{Graph, HeuristicReduce},
-    io::{Config, Algorithm},
+    io::{self, Config, Algorithm},
 };
+use checkpoint::Checkpointer;
 use grb::prelude::*;
 
 mod bnb;
+mod branching;
+mod checkpoint;
+pub mod export;
+pub mod hs_solver;
+mod pool;
+mod sat;
+mod ihs;
 // mod cycle_ilp;
 // mod ilp;
 mod splitter;
+mod transposition;
 // mod heur_ilp;
 // mod hybrid_ilp;
 // mod reduce_ilp;
@@ -16,21 +25,69 @@ mod vc_solver;
 // mod vcsr_ilp;
 
 mod grb_ilp;
+mod order_ilp;
 pub fn solve(mut graph: Graph, config: &Config) -> Vec<u32> {
+    if let Some(path) = config.initial_solution() {
+        match io::load_solution(path, graph.total_vertices()) {
+            Ok(initial) if graph.is_acyclic_with_fvs(&initial) => {}
+            Ok(_) => {
+                eprintln!("the initial solution is not a DFVS");
+                std::process::exit(1);
+            }
+            Err(err) => {
+                eprintln!("cannot read the initial solution: {}", err);
+                std::process::exit(1);
+            }
+        }
+    }
 
-    let mut solution = if config.reduce() {
+    let solution = if config.reduce() {
         graph.reduce()
     } else {
         Vec::new()
     };
 
+    let checkpointer = Checkpointer::new(config, &solution, &graph);
+    solve_reduced(graph, solution, checkpointer, config)
+}
+
+/// Continues the run that wrote the checkpoint file given in `config`, which
+/// fails if the file cannot be read.
+pub fn resume(config: &Config) -> std::io::Result<Vec<u32>> {
+    let (solution, graph, checkpointer) = Checkpointer::resume(config)?;
+    Ok(solve_reduced(graph, solution, checkpointer, config))
+}
+
+/// Returns the initial solution of `config`, which [`solve`] has checked
+/// already. A run that is resumed ignores a file that cannot be read.
+fn initial_solution(config: &Config, vertices: usize) -> Option<Vec<u32>> {
+    let path = config.initial_solution().as_ref()?;
+    match io::load_solution(path, vertices) {
+        Ok(solution) => Some(solution),
+        Err(err) => {
+            eprintln!("ignoring the initial solution: {}", err);
+            None
+        }
+    }
+}
+
+fn solve_reduced(
+    mut graph: Graph,
+    mut solution: Vec<u32>,
+    mut checkpointer: Checkpointer,
+    config: &Config,
+) -> Vec<u32> {
     if graph.vertices() == 0 {
         return solution;
     }
 
     let mut remaining = match config.algorithm() {
-        Algorithm::ILP => grb_ilp::solve(graph, config),
-        _ => bnb::solve(&mut graph, config),
+        Algorithm::ILP => grb_ilp::solve(graph, config, &mut checkpointer),
+        Algorithm::BNC => grb_ilp::branch_and_cut(graph, config, &mut checkpointer),
+        Algorithm::MTZ => order_ilp::solve(graph, config),
+        Algorithm::SAT => sat::solve(graph, config),
+        Algorithm::IHS => ihs::solve(graph, config),
+        _ => bnb::solve(&mut graph, config, &mut checkpointer),
     };
 
     solution.append(&mut remaining);
@@ -0,0 +1,67 @@
+//! Ordering formulation of the DFVS problem. Besides its binary variable,
+//! every vertex gets a potential, and every arc u -> v between kept vertices
+//! has to increase the potential: `p_v >= p_u + 1 - n (x_u + x_v)`. The kept
+//! vertices are then ordered topologically, so unlike the cycle formulation of
+//! [`super::grb_ilp`] the model is complete from the start and solved once.
+use super::{
+    grb_ilp::{add_symmetry_rows, build_model},
+    recover_solution,
+    splitter::split_reduction,
+};
+use crate::{graph::Graph, io::Config};
+use grb::prelude::*;
+
+pub fn solve(graph: Graph, config: &Config) -> Vec<u32> {
+    let _out = shh::stdout();
+    let vertices = graph.total_vertices();
+    let data = split_reduction(graph, config);
+
+    // the constraints of the split reduction are valid cycle rows, which
+    // strengthen the weak relaxation of the potentials
+    let (mut model, vars, delta) = build_model(
+        vertices,
+        &data.constraints,
+        &data.undirected_graph,
+        &data.upper_bound,
+    );
+    if config.symmetry() {
+        add_symmetry_rows(&mut model, &vars, &data.directed_graph, &data.undirected_graph);
+    }
+
+    let big_m = vertices as f64;
+    let mut potentials = Vec::with_capacity(vertices);
+    for i in 0..vertices {
+        let n = format!("p{}", i);
+        let var = add_ctsvar!(model, name: &n, bounds: 0.0..big_m).unwrap();
+        potentials.push(var);
+    }
+
+    let graph = &data.directed_graph;
+    let mut arcs = 0;
+    for u in graph.get_active_vertices() {
+        for v in graph.get_outgoing(&u) {
+            let (xu, xv) = (vars[u as usize], vars[*v as usize]);
+            let (pu, pv) = (potentials[u as usize], potentials[*v as usize]);
+            model
+                .add_constr("", c!(pv - pu + big_m * xu + big_m * xv >= 1))
+                .unwrap();
+            arcs += 1;
+        }
+    }
+
+    // the upper bound is a DFVS of the directed graph, the remainder gives
+    // the potentials of the start
+    for (position, v) in graph.topological_order(&data.upper_bound).iter().enumerate() {
+        model
+            .set_obj_attr(attr::Start, &potentials[*v as usize], position as f64)
+            .unwrap();
+    }
+
+    model.optimize().unwrap();
+    let mut dfvs = Vec::new();
+    recover_solution(&model, &vars, &mut dfvs);
+    eprintln!("{}", data.constraints.len() + delta + arcs);
+    eprintln!("iters = 1");
+    dfvs.extend_from_slice(&data.split_reduced);
+    dfvs
+}
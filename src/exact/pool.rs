@@ -0,0 +1,169 @@
+//! Pool of the constraints of the iterated ILP. Every solve adds cycle rows,
+//! most of which are no longer tight after a few more solves. A row whose
+//! slack stays positive for `max_age` consecutive solves is removed from the
+//! model but kept in a cache, and comes back as soon as a solution violates
+//! it.
+use crate::util::Constraint;
+use grb::{expr::LinExpr, prelude::*};
+
+pub struct ConstraintPool {
+    /// The rows in the model, with the number of consecutive solves in which
+    /// they had slack and their handle.
+    active: Vec<Constraint>,
+    ages: Vec<usize>,
+    handles: Vec<Constr>,
+
+    /// The rows removed from the model.
+    cached: Vec<Constraint>,
+    max_age: usize,
+}
+
+impl ConstraintPool {
+    pub fn new(max_age: usize) -> ConstraintPool {
+        ConstraintPool {
+            active: Vec::new(),
+            ages: Vec::new(),
+            handles: Vec::new(),
+            cached: Vec::new(),
+            max_age,
+        }
+    }
+
+    /// Returns the rows that are currently in the model.
+    pub fn active(&self) -> &Vec<Constraint> {
+        &self.active
+    }
+
+    pub fn len(&self) -> usize {
+        self.active.len() + self.cached.len()
+    }
+
+    /// Adds `constraint` to the pool and to the model.
+    pub fn add(&mut self, model: &mut Model, vars: &[Var], constraint: Constraint) {
+        let mut expr = LinExpr::new();
+        for v in constraint.variables() {
+            expr.add_term(1., vars[*v as usize]);
+        }
+        let constr = model.add_constr("", c!(expr >= constraint.lower_bound())).unwrap();
+        self.active.push(constraint);
+        self.ages.push(0);
+        self.handles.push(constr);
+    }
+
+    /// Ages the rows with respect to the solution `dfvs` of the last solve,
+    /// and removes the rows that had slack for too long from the model.
+    /// Returns the number of removed rows.
+    pub fn age(&mut self, model: &mut Model, dfvs: &[u32], vertices: usize) -> usize {
+        let evicted = self.evict(&membership(dfvs, vertices));
+        for i in &evicted {
+            model.remove(self.handles.swap_remove(*i)).unwrap();
+        }
+        evicted.len()
+    }
+
+    /// Adds the cached rows violated by the solution `dfvs` back to the model.
+    /// Returns the number of restored rows.
+    pub fn restore(&mut self, model: &mut Model, vars: &[Var], dfvs: &[u32]) -> usize {
+        let restored = self.reinstate(&membership(dfvs, vars.len()));
+        let count = restored.len();
+        for constraint in restored {
+            self.add(model, vars, constraint);
+        }
+        count
+    }
+
+    /// Ages the active rows and moves the rows that had slack in more than
+    /// `max_age` consecutive solves to the cache. Returns their indices in
+    /// decreasing order, such that removing the handles at these indices with
+    /// `swap_remove` keeps the handles in line with the rows.
+    fn evict(&mut self, in_solution: &[bool]) -> Vec<usize> {
+        let mut evicted = Vec::new();
+        for (i, constraint) in self.active.iter().enumerate() {
+            if hits(constraint, in_solution) > constraint.lower_bound() as usize {
+                self.ages[i] += 1;
+            } else {
+                self.ages[i] = 0;
+            }
+            if self.ages[i] > self.max_age {
+                evicted.push(i);
+            }
+        }
+
+        evicted.reverse();
+        for i in &evicted {
+            self.ages.swap_remove(*i);
+            self.cached.push(self.active.swap_remove(*i));
+        }
+        evicted
+    }
+
+    /// Takes the cached rows that the solution violates out of the cache.
+    fn reinstate(&mut self, in_solution: &[bool]) -> Vec<Constraint> {
+        let (violated, cached) = std::mem::take(&mut self.cached)
+            .into_iter()
+            .partition(|constraint| hits(constraint, in_solution) < constraint.lower_bound() as usize);
+        self.cached = cached;
+        violated
+    }
+}
+
+fn membership(dfvs: &[u32], vertices: usize) -> Vec<bool> {
+    let mut in_solution = vec![false; vertices];
+    for v in dfvs {
+        in_solution[*v as usize] = true;
+    }
+    in_solution
+}
+
+/// Returns the number of variables of `constraint` that are in the solution.
+fn hits(constraint: &Constraint, in_solution: &[bool]) -> usize {
+    constraint
+        .variables()
+        .iter()
+        .filter(|v| in_solution[**v as usize])
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hits_test_001() {
+        // a row has slack once more of its variables than needed are chosen
+        let constraint = Constraint::new(vec![0, 2, 3], 1);
+        let in_solution = membership(&[2, 3], 5);
+        assert_eq!(hits(&constraint, &in_solution), 2);
+        let in_solution = membership(&[1, 4], 5);
+        assert_eq!(hits(&constraint, &in_solution), 0);
+    }
+
+    #[test]
+    fn evict_test_001() {
+        // the row over 0 and 1 has slack whenever both are chosen
+        let max_age = 2;
+        let mut pool = ConstraintPool::new(max_age);
+        pool.active.push(Constraint::new(vec![0, 1], 1));
+        pool.ages.push(0);
+        pool.active.push(Constraint::new(vec![1, 2], 1));
+        pool.ages.push(0);
+
+        let slack = membership(&[0, 1], 3);
+        for _ in 0..max_age {
+            assert!(pool.evict(&slack).is_empty());
+            assert!(pool.reinstate(&slack).is_empty());
+        }
+        assert_eq!(pool.evict(&slack), vec![0]);
+        assert_eq!(pool.active().len(), 1);
+        assert_eq!(pool.active()[0].variables(), &[1, 2]);
+        assert_eq!(pool.len(), 2);
+
+        // the cached row stays out while it is hit, and returns once it is not
+        assert!(pool.reinstate(&slack).is_empty());
+        let violating = membership(&[2], 3);
+        let restored = pool.reinstate(&violating);
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].variables(), &[0, 1]);
+        assert_eq!(pool.len(), 1);
+    }
+}
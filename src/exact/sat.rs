@@ -0,0 +1,89 @@
+//! Exact backend on the SAT solver of [`crate::util`], which needs no MIP
+//! solver. A variable per vertex is true if the vertex is deleted, every cycle
+//! and undirected edge of the split reduction is a clause, and a totalizer
+//! bounds the number of deletions. Whenever the solver finds an assignment
+//! that leaves cycles, clauses for those cycles are added. Whenever it finds a
+//! DFVS, the bound is decreased below its size, until no assignment is left.
+use super::splitter::split_reduction;
+use crate::{
+    graph::{EdgeCycleCover, Graph},
+    io::Config,
+    util::{Lit, Solver, Totalizer},
+};
+
+pub fn solve(graph: Graph, config: &Config) -> Vec<u32> {
+    let vertices = graph.total_vertices();
+    let data = split_reduction(graph, config);
+    let graph = &data.directed_graph;
+    let undirected_graph = &data.undirected_graph;
+
+    let mut solver = Solver::new();
+    let vars: Vec<_> = (0..vertices).map(|_| solver.new_var()).collect();
+    let mut clauses = 0;
+    for constraint in &data.constraints {
+        debug_assert_eq!(constraint.lower_bound(), 1);
+        add_cycle(&mut solver, &vars, constraint.variables());
+        clauses += 1;
+    }
+
+    // only the vertices of either part can be needed in a solution
+    let candidates: Vec<_> = (0..vertices as u32)
+        .filter(|v| {
+            !graph.get_outgoing(v).is_empty()
+                || !graph.get_incoming(v).is_empty()
+                || !undirected_graph.get_outgoing(v).is_empty()
+        })
+        .collect();
+
+    let mut best = data.upper_bound;
+    for v in &best {
+        solver.set_phase(vars[*v as usize], true);
+    }
+
+    let mut iters = 0;
+    if !best.is_empty() {
+        let inputs: Vec<_> = candidates.iter().map(|v| Lit::positive(vars[*v as usize])).collect();
+        let totalizer = Totalizer::new(&mut solver, &inputs, best.len());
+        // the lower bound of the split reduction may already prove the upper
+        // bound optimal
+        'bound: while best.len() > data.lower_bound {
+            solver.add_clause(&[!totalizer.at_least(best.len())]);
+            loop {
+                iters += 1;
+                if !solver.solve() {
+                    break 'bound;
+                }
+
+                let dfvs: Vec<_> = candidates
+                    .iter()
+                    .copied()
+                    .filter(|v| solver.value(vars[*v as usize]))
+                    .collect();
+                if graph.is_acyclic_with_fvs(&dfvs) {
+                    best = dfvs;
+                    break;
+                }
+
+                let mut cycles = graph.disjoint_edge_cycle_cover(&dfvs);
+                if cycles.is_empty() {
+                    cycles.push(graph.find_cycle_with_fvs(&dfvs).unwrap());
+                }
+                for cycle in cycles {
+                    add_cycle(&mut solver, &vars, &cycle);
+                    clauses += 1;
+                }
+            }
+        }
+    }
+
+    eprintln!("{}", clauses);
+    eprintln!("iters = {}", iters);
+    best.extend_from_slice(&data.split_reduced);
+    best
+}
+
+/// Adds the clause that at least one vertex of `cycle` is deleted.
+fn add_cycle(solver: &mut Solver, vars: &[u32], cycle: &[u32]) {
+    let clause: Vec<_> = cycle.iter().map(|v| Lit::positive(vars[*v as usize])).collect();
+    solver.add_clause(&clause);
+}
@@ -2,7 +2,9 @@ use rustc_hash::FxHashSet;
 
 use crate::{
     graph::{EdgeCycleCover, Graph, Reducable},
-    util::Constraint, heur::hitting_set_upper_bound, io::Config,
+    util::Constraint,
+    heur::{extractor, restrict_solution, AnnealingOracle, Heuristic, ImplicitHittingSet, SimulatedAnnealing},
+    io::Config,
 };
 
 pub struct ILPData {
@@ -15,6 +17,9 @@ pub struct ILPData {
     /// An upper bound for `constraints`
     pub upper_bound: Vec<u32>,
 
+    /// A lower bound for `constraints`, and thus for the DFVS of both graphs
+    pub lower_bound: usize,
+
     /// The completely directed graph generated in the split reduction
     pub directed_graph: Graph,
 
@@ -30,6 +35,18 @@ pub fn split_reduction(mut graph: Graph, config: &Config) -> ILPData {
     let mut forced = Vec::new();
     let mut undirected_graph = Graph::new(vertices);
 
+    // The size of any DFVS allows the petal rule to force vertices that are
+    // on too many disjoint cycles.
+    let initial = super::initial_solution(config, vertices);
+    let budget = if config.reduce() {
+        match &initial {
+            Some(solution) => SimulatedAnnealing::upper_bound_from(&graph, solution).len(),
+            None => SimulatedAnnealing::upper_bound(&graph).len(),
+        }
+    } else {
+        vertices
+    };
+
     loop {
         let stars = graph.stars();
         if stars.is_empty() {
@@ -57,7 +74,7 @@ pub fn split_reduction(mut graph: Graph, config: &Config) -> ILPData {
             break;
         }
 
-        let mut reduced = graph.reduce(vertices).unwrap();
+        let mut reduced = graph.reduce(budget - forced.len()).unwrap();
         if reduced.is_empty() {
             break;
         }
@@ -85,19 +102,27 @@ pub fn split_reduction(mut graph: Graph, config: &Config) -> ILPData {
         hitting_set.push(Constraint::new(cycle, 1));
     }
 
-    let mut upper_bound = hitting_set_upper_bound(&hitting_set, vertices);
-    while !graph.is_acyclic_with_fvs(&upper_bound) {
-        let cycles = graph.disjoint_edge_cycle_cover(&upper_bound);
-        for cycle in cycles {
-            hitting_set.push(Constraint::new(cycle, 1));
+    let oracle = AnnealingOracle::new(vertices);
+    let mut ihs = ImplicitHittingSet::new(oracle, extractor(config.cores()), hitting_set, vertices);
+    let mut upper_bound = ihs.solve(&graph, |_| {});
+    let lower_bound = ihs.lower_bound();
+    let hitting_set = ihs.into_constraints();
+
+    // the initial solution is a DFVS of both parts, and thus hits every
+    // constraint
+    if let Some(solution) = &initial {
+        let combined = graph.clone() + undirected_graph.clone();
+        let restricted = restrict_solution(&combined, solution);
+        if restricted.len() < upper_bound.len() {
+            upper_bound = restricted;
         }
-        upper_bound = hitting_set_upper_bound(&hitting_set, vertices);
     }
 
     ILPData {
         split_reduced: forced,
         constraints: hitting_set,
         upper_bound,
+        lower_bound,
         directed_graph: graph,
         undirected_graph,
     }
@@ -0,0 +1,176 @@
+//! Transposition table of the branch-and-reduce algorithm. Different branches
+//! often reduce to the same residual graph, whose results are stored under a
+//! hash of its arcs. The least recently used entries are evicted once the
+//! table exceeds its memory limit.
+use crate::graph::Graph;
+use rustc_hash::{FxHashMap, FxHasher};
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap},
+    hash::{Hash, Hasher},
+    mem::size_of,
+};
+
+/// Two independent hashes of a residual graph, which makes collisions
+/// negligible without storing the graph itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Key(u64, u64);
+
+impl Key {
+    /// Returns the key of the residual graph given by the directed graph `gd`
+    /// and the undirected graph `gb` of the split. Vertices without arcs do
+    /// not affect the solutions, so only the adjacency of the other vertices
+    /// is hashed.
+    pub fn new(gd: &Graph, gb: &Graph) -> Key {
+        let mut fx = FxHasher::default();
+        let mut sip = DefaultHasher::new();
+        for v in 0..gd.total_vertices() as u32 {
+            let directed = gd.get_outgoing(&v);
+            let undirected = gb.get_outgoing(&v);
+            if directed.is_empty() && undirected.is_empty() {
+                continue;
+            }
+            for hasher in [&mut fx as &mut dyn Hasher, &mut sip] {
+                hasher.write_u32(v);
+                hasher.write_usize(directed.len());
+                directed.iter().for_each(|u| hasher.write_u32(*u));
+                hasher.write_usize(undirected.len());
+                undirected.iter().for_each(|u| hasher.write_u32(*u));
+            }
+        }
+        Key(fx.finish(), sip.finish())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Entry {
+    /// An optimal solution of the residual graph.
+    Optimal(Vec<u32>),
+
+    /// A lower bound on the size of the solutions of the residual graph.
+    LowerBound(usize),
+}
+
+impl Entry {
+    fn size(&self) -> usize {
+        match self {
+            Entry::Optimal(solution) => solution.len() * size_of::<u32>(),
+            Entry::LowerBound(_) => 0,
+        }
+    }
+}
+
+pub struct TranspositionTable {
+    /// The entries together with the time of their last use.
+    entries: FxHashMap<Key, (Entry, u64)>,
+
+    /// The keys ordered by the time of their last use.
+    order: BTreeMap<u64, Key>,
+    clock: u64,
+
+    /// The estimated number of bytes used by the table, and its limit.
+    memory: usize,
+    limit: usize,
+}
+
+/// The bytes used by an entry besides its solution.
+const ENTRY_OVERHEAD: usize = 2 * size_of::<Key>() + size_of::<(Entry, u64)>() + 2 * size_of::<u64>();
+
+impl TranspositionTable {
+    pub fn new(limit: usize) -> TranspositionTable {
+        TranspositionTable {
+            entries: FxHashMap::default(),
+            order: BTreeMap::new(),
+            clock: 0,
+            memory: 0,
+            limit,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn touch(&mut self, key: Key) {
+        let (_, used) = self.entries.get_mut(&key).unwrap();
+        self.order.remove(used);
+        *used = self.clock;
+        self.order.insert(self.clock, key);
+        self.clock += 1;
+    }
+
+    pub fn get(&mut self, key: &Key) -> Option<Entry> {
+        if !self.entries.contains_key(key) {
+            return None;
+        }
+        self.touch(*key);
+        Some(self.entries[key].0.clone())
+    }
+
+    /// Stores `entry` for `key`. An optimal solution is never replaced, and
+    /// of two lower bounds the larger one is kept.
+    pub fn insert(&mut self, key: Key, entry: Entry) {
+        if let Some((old, _)) = self.entries.get_mut(&key) {
+            let better = match (&*old, &entry) {
+                (Entry::Optimal(_), _) => false,
+                (Entry::LowerBound(_), Entry::Optimal(_)) => true,
+                (Entry::LowerBound(a), Entry::LowerBound(b)) => b > a,
+            };
+            if better {
+                self.memory = self.memory - old.size() + entry.size();
+                *old = entry;
+            }
+            self.touch(key);
+        } else {
+            self.memory += ENTRY_OVERHEAD + entry.size();
+            self.entries.insert(key, (entry, 0));
+            self.touch(key);
+        }
+
+        while self.memory > self.limit {
+            let (_, oldest) = self.order.pop_first().unwrap();
+            let (entry, _) = self.entries.remove(&oldest).unwrap();
+            self.memory -= ENTRY_OVERHEAD + entry.size();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_test_001() {
+        // isolated and deleted vertices do not change the key
+        let mut gd = Graph::new(4);
+        gd.add_arc(0, 1);
+        gd.add_arc(1, 0);
+        let gb = Graph::new(4);
+        let mut other = gd.clone();
+        other.add_arc(2, 3);
+        other.remove_vertex(3);
+        assert_eq!(Key::new(&gd, &gb), Key::new(&other, &gb));
+
+        other.add_arc(1, 2);
+        assert_ne!(Key::new(&gd, &gb), Key::new(&other, &gb));
+    }
+
+    #[test]
+    fn transposition_table_test_001() {
+        // room for exactly two entries, the least recently used is evicted
+        let mut table = TranspositionTable::new(2 * ENTRY_OVERHEAD);
+        table.insert(Key(0, 0), Entry::LowerBound(1));
+        table.insert(Key(1, 1), Entry::LowerBound(2));
+        assert_eq!(table.get(&Key(0, 0)), Some(Entry::LowerBound(1)));
+        table.insert(Key(2, 2), Entry::LowerBound(3));
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.get(&Key(1, 1)), None);
+        assert_eq!(table.get(&Key(0, 0)), Some(Entry::LowerBound(1)));
+
+        // lower bounds only increase, and optimal solutions are kept
+        table.insert(Key(0, 0), Entry::LowerBound(0));
+        assert_eq!(table.get(&Key(0, 0)), Some(Entry::LowerBound(1)));
+        table.insert(Key(2, 2), Entry::Optimal(vec![]));
+        table.insert(Key(2, 2), Entry::LowerBound(5));
+        assert_eq!(table.get(&Key(2, 2)), Some(Entry::Optimal(vec![])));
+    }
+}
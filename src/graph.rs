@@ -9,6 +9,20 @@ use rand::SeedableRng;
 use rustc_hash::{FxHashMap, FxHashSet};
 use std::{collections::VecDeque, fmt::Write, ops::Add};
 
+mod clique_cover;
+mod dominator;
+mod flow;
+mod symmetry;
+mod vertex_cover;
+mod weighted_cycles;
+pub use clique_cover::CliqueCover;
+pub use dominator::Dominators;
+pub use flow::Petals;
+use flow::FlowNetwork;
+pub use symmetry::Symmetry;
+pub use vertex_cover::{Folds, UndirectedReduce};
+pub use weighted_cycles::WeightedCycles;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Graph {
     /// The list of vertices which are deleted.
@@ -344,6 +358,45 @@ impl Graph {
         true
     }
 
+    /// Returns the active vertices outside `fvs` in topological order,
+    /// assuming that `fvs` is a DFVS.
+    pub fn topological_order(&self, fvs: &[u32]) -> Vec<u32> {
+        let mut removed = self.deleted_vertices.clone();
+        for v in fvs {
+            removed[*v as usize] = true;
+        }
+
+        let mut in_degree = vec![0; self.total_vertices()];
+        for u in 0..self.total_vertices() {
+            if removed[u] {
+                continue;
+            }
+            for v in &self.adj[u] {
+                if !removed[*v as usize] {
+                    in_degree[*v as usize] += 1;
+                }
+            }
+        }
+
+        let mut order: Vec<_> = (0..self.total_vertices() as u32)
+            .filter(|v| !removed[*v as usize] && in_degree[*v as usize] == 0)
+            .collect();
+        let mut i = 0;
+        while i < order.len() {
+            for v in &self.adj[order[i] as usize] {
+                if removed[*v as usize] {
+                    continue;
+                }
+                in_degree[*v as usize] -= 1;
+                if in_degree[*v as usize] == 0 {
+                    order.push(*v);
+                }
+            }
+            i += 1;
+        }
+        order
+    }
+
     fn recover_cycle(vertex: u32, dest: u32, pred: &Vec<Option<u32>>) -> Vec<u32> {
         let mut path = Vec::new();
         let mut current_vertex = vertex;
@@ -753,6 +806,82 @@ impl Graph {
         }
     }
 
+    /// Removes `vertex` and connects each of its in-neighbours to each of its
+    /// out-neighbours, which may introduce self-loops.
+    fn bypass_vertex(&mut self, vertex: u32) {
+        let sources = self.rev_adj[vertex as usize].clone();
+        let targets = self.adj[vertex as usize].clone();
+        self.remove_vertex(vertex);
+        for source in &sources {
+            for target in &targets {
+                self.add_arc(*source, *target);
+            }
+        }
+    }
+
+    /// Returns all vertices with more than `upper_bound` cycles that pairwise
+    /// only share that vertex. Such a vertex must be in every DFVS of size at
+    /// most `upper_bound`. Only vertices with both in- and outdegree larger
+    /// than `upper_bound` can have such a petal number.
+    fn petal_forced(&self, upper_bound: usize) -> Vec<u32> {
+        let mut forced = Vec::new();
+        // the network is only built once a vertex has a large enough degree
+        let mut network = None;
+        for i in 0..self.total_vertices() {
+            if self.deleted_vertices[i]
+                || self.adj[i].len() <= upper_bound
+                || self.rev_adj[i].len() <= upper_bound
+            {
+                continue;
+            }
+            let network = network.get_or_insert_with(|| FlowNetwork::vertex_split(self));
+            if network.petal_number(i, upper_bound + 1) > upper_bound {
+                forced.push(i as u32);
+            }
+        }
+        forced
+    }
+
+    /// Removes all vertices that are not forbidden and have petal number 0,
+    /// i.e., that lie on no cycle. By Menger's theorem, the vertices with
+    /// petal number 1 are those with a cycle dominator, which are bypassed by
+    /// the dominator reduction instead.
+    fn petal_zero_reduction(&mut self) -> bool {
+        let petals = self.petal_numbers(1);
+        let acyclic: Vec<_> = (0..self.total_vertices() as u32)
+            .filter(|v| {
+                let i = *v as usize;
+                // the network ignores self-loops
+                !self.deleted_vertices[i] && !self.forbidden[i] && petals[i] == 0 && self.adj[i].binary_search(v).is_err()
+            })
+            .collect();
+        self.remove_vertices(&acyclic);
+        !acyclic.is_empty()
+    }
+
+    /// Bypasses all vertices *u* that are not forbidden and for which another
+    /// vertex *v* lies on every cycle through *u*, i.e., *v* dominates *u* in
+    /// the graph where *u* is split into a source and a sink. A DFVS
+    /// containing *u* remains a DFVS if *u* is replaced by *v*. This
+    /// generalizes the single incoming and single outgoing reductions.
+    fn dominator_reduction(&mut self) -> bool {
+        let mut reduced = false;
+        for i in 0..self.total_vertices() {
+            if self.deleted_vertices[i]
+                || self.forbidden[i]
+                || self.adj[i].is_empty()
+                || self.rev_adj[i].is_empty()
+            {
+                continue;
+            }
+            if !self.cycle_dominators(i as u32).is_empty() {
+                self.bypass_vertex(i as u32);
+                reduced = true;
+            }
+        }
+        reduced
+    }
+
     /// Finds vertices contained in a 2-cycle and all its neighbors that are
     /// included in the 2-cycles
     pub fn stars(&self) -> Vec<(u32, Vec<u32>)> {
@@ -972,8 +1101,9 @@ impl Graph {
         let mut induced = self.clone();
 
         for i in 0..induced.total_vertices() {
-            if !subset.contains(&(i as u32)) {
+            if subset.binary_search(&(i as u32)).is_err() {
                 induced.adj[i].clear();
+                induced.rev_adj[i].clear();
                 induced.deleted_vertices[i] = true;
                 continue;
             }
@@ -1019,6 +1149,22 @@ impl Graph {
             self.forbidden[*vertex as usize] = true;
         }
     }
+
+    /// Contracts `vertices`, which may not be part of the solution, by
+    /// bypassing them one after another. Returns `false` if one of them ends
+    /// up on a self-loop, in which case there is no such solution.
+    pub fn contract_undeletable(&mut self, vertices: &[u32]) -> bool {
+        for vertex in vertices {
+            if self.deleted_vertices[*vertex as usize] {
+                continue;
+            }
+            if self.adj[*vertex as usize].binary_search(vertex).is_ok() {
+                return false;
+            }
+            self.bypass_vertex(*vertex);
+        }
+        true
+    }
 }
 
 pub trait Reducable {
@@ -1063,6 +1209,28 @@ impl Reducable for Graph {
                 forced.append(&mut self_loops);
                 continue;
             }
+
+            let mut petal_forced = self.petal_forced(upper_bound);
+            if !petal_forced.is_empty() {
+                if petal_forced.len() > upper_bound {
+                    return None;
+                }
+                self.remove_vertices(&petal_forced);
+                reduced = true;
+                upper_bound -= petal_forced.len();
+                forced.append(&mut petal_forced);
+                continue;
+            }
+
+            if self.petal_zero_reduction() {
+                reduced = true;
+                continue;
+            }
+
+            if self.dominator_reduction() {
+                reduced = true;
+                continue;
+            }
         }
         Some(forced)
     }
@@ -1104,6 +1272,16 @@ impl HeuristicReduce for Graph {
                 forced.append(&mut self_loops);
                 continue;
             }
+
+            if self.petal_zero_reduction() {
+                reduced = true;
+                continue;
+            }
+
+            if self.dominator_reduction() {
+                reduced = true;
+                continue;
+            }
         }
         forced
     }
@@ -1850,15 +2028,21 @@ impl FourCliques for Graph {
 }
 
 pub trait SplitReduce {
-    fn split_reduce(self) -> (Graph, Graph, Vec<u32>);
+    /// Splits the graph into its directed and undirected part while reducing
+    /// the directed part, and the undirected-only vertices with vertex cover
+    /// rules. A solution of the reduced graphs, extended with the forced
+    /// vertices, is lifted back with the returned folds. Returns `None` if
+    /// more than `upper_bound` vertices are forced.
+    fn split_reduce(self, upper_bound: usize) -> Option<(Graph, Graph, Vec<u32>, Folds)>;
     fn split(self) -> (Graph, Graph);
 }
 
 impl SplitReduce for Graph {
-    fn split_reduce(mut self) -> (Graph, Graph, Vec<u32>) {
+    fn split_reduce(mut self, upper_bound: usize) -> Option<(Graph, Graph, Vec<u32>, Folds)> {
         let vertices = self.total_vertices();
         let mut undirected_graph = Graph::new(vertices);
-        let mut forced = Reducable::reduce(&mut self, vertices).unwrap();
+        let mut forced = Reducable::reduce(&mut self, upper_bound)?;
+        let mut folds = Folds::default();
 
         let mut id = 0;
         let mut constraint_map = vec![Vec::new(); vertices];
@@ -1866,7 +2050,25 @@ impl SplitReduce for Graph {
         loop {
             let stars = self.stars();
             if stars.is_empty() {
-                break;
+                // The directed part is reduced, try the undirected-only vertices
+                for vertex in &forced {
+                    undirected_graph.remove_vertex(*vertex);
+                }
+                let previous_folds = folds.len();
+                let mut covered =
+                    undirected_graph.vertex_cover_reduction(&mut self, &mut folds);
+                if covered.is_empty() && folds.len() == previous_folds {
+                    break;
+                }
+                forced.append(&mut covered);
+                if forced.len() + folds.len() > upper_bound {
+                    return None;
+                }
+
+                let budget = upper_bound - forced.len() - folds.len();
+                let mut reduced = Reducable::reduce(&mut self, budget)?;
+                forced.append(&mut reduced);
+                continue;
             }
 
             let mut sources = Vec::with_capacity(stars.len());
@@ -1886,10 +2088,8 @@ impl SplitReduce for Graph {
             self.mark_forbidden(&sources);
             self.remove_undirected_edges(stars);
 
-            let mut reduced = Reducable::reduce(&mut self, vertices).unwrap();
-            if reduced.is_empty() {
-                break;
-            }
+            let budget = upper_bound.checked_sub(forced.len() + folds.len())?;
+            let mut reduced = Reducable::reduce(&mut self, budget)?;
             forced.append(&mut reduced);
         }
 
@@ -1902,7 +2102,7 @@ impl SplitReduce for Graph {
             undirected_graph.remove_vertex(*vertex);
         }
 
-        (self, undirected_graph, forced)
+        Some((self, undirected_graph, forced, folds))
     }
 
     fn split(mut self) -> (Graph, Graph) {
@@ -2033,6 +2233,36 @@ mod tests {
         graph.reduce(2);
     }
 
+    #[test]
+    fn topological_order_test_001() {
+        // removing 2 breaks the cycle 0 -> 1 -> 2 -> 0
+        let mut graph = Graph::new(4);
+        graph.add_arc(0, 1);
+        graph.add_arc(1, 2);
+        graph.add_arc(2, 0);
+        graph.add_arc(3, 1);
+        let order = graph.topological_order(&[2]);
+        assert_eq!(order.len(), 3);
+        let position = |v: u32| order.iter().position(|u| *u == v).unwrap();
+        assert!(position(0) < position(1));
+        assert!(position(3) < position(1));
+    }
+
+    #[test]
+    fn contract_undeletable_test_001() {
+        // keeping 0 and 1 of a triangle with a 2-cycle 1 <-> 3 forces 2 and 3
+        let mut graph = Graph::new(4);
+        graph.add_arc(0, 1);
+        graph.add_arc(1, 2);
+        graph.add_arc(2, 0);
+        graph.add_arc(1, 3);
+        graph.add_arc(3, 1);
+        assert!(graph.contract_undeletable(&[0, 1]));
+        assert_eq!(graph.get_outgoing(&2), &[2, 3]);
+        assert_eq!(graph.get_outgoing(&3), &[2, 3]);
+        assert!(!graph.contract_undeletable(&[2]));
+    }
+
     #[test]
     fn scc_test_001() {
         let mut graph = Graph::new(3);
@@ -0,0 +1,113 @@
+//! Clique inequalities for the undirected part of a split graph. Every two
+//! vertices of a clique *C* of bidirected edges form a cycle, so at most one
+//! vertex of *C* is kept: *sum over C >= |C| - 1*. Every edge is extended to a
+//! maximal clique within the common neighbourhood of its endpoints, exactly if
+//! the neighbourhood is small and greedily otherwise.
+use super::Graph;
+use crate::util::algorithms::intersection;
+use rustc_hash::FxHashSet;
+
+/// Common neighbourhoods up to this size are searched for a maximum clique.
+const EXACT_LIMIT: usize = 16;
+
+pub trait CliqueCover {
+    /// Returns cliques of at least three vertices of the undirected graph,
+    /// such that every edge in a triangle is covered by one of them.
+    fn clique_cover(&self) -> Vec<Vec<u32>>;
+}
+
+impl CliqueCover for Graph {
+    fn clique_cover(&self) -> Vec<Vec<u32>> {
+        let mut covered = FxHashSet::default();
+        let mut cliques = Vec::new();
+        for u in 0..self.total_vertices() as u32 {
+            for v in &self.adj[u as usize] {
+                if *v < u || covered.contains(&(u, *v)) {
+                    continue;
+                }
+                let candidates = intersection(&self.adj[u as usize], &self.adj[*v as usize]);
+                if candidates.is_empty() {
+                    continue;
+                }
+
+                let mut clique = if candidates.len() <= EXACT_LIMIT {
+                    let mut best = Vec::new();
+                    self.maximum_clique(&mut Vec::new(), &candidates, &mut best);
+                    best
+                } else {
+                    self.greedy_clique(candidates)
+                };
+                clique.push(u);
+                clique.push(*v);
+                clique.sort_unstable();
+                for i in 0..clique.len() {
+                    for j in i + 1..clique.len() {
+                        covered.insert((clique[i], clique[j]));
+                    }
+                }
+                cliques.push(clique);
+            }
+        }
+        cliques
+    }
+}
+
+impl Graph {
+    /// Extends `current` by a maximum clique within the sorted `candidates`,
+    /// and stores the result in `best` if it is larger.
+    fn maximum_clique(&self, current: &mut Vec<u32>, candidates: &[u32], best: &mut Vec<u32>) {
+        if candidates.is_empty() {
+            if current.len() > best.len() {
+                *best = current.clone();
+            }
+            return;
+        }
+        for i in 0..candidates.len() {
+            if current.len() + candidates.len() - i <= best.len() {
+                return;
+            }
+            let w = candidates[i];
+            let remaining = intersection(&candidates[i + 1..], &self.adj[w as usize]);
+            current.push(w);
+            self.maximum_clique(current, &remaining, best);
+            current.pop();
+        }
+    }
+
+    /// Returns a maximal clique within `candidates`, repeatedly taking the
+    /// candidate with the most neighbours among the others.
+    fn greedy_clique(&self, mut candidates: Vec<u32>) -> Vec<u32> {
+        let mut clique = Vec::new();
+        while !candidates.is_empty() {
+            let w = *candidates
+                .iter()
+                .max_by_key(|w| intersection(&candidates, &self.adj[**w as usize]).len())
+                .unwrap();
+            clique.push(w);
+            candidates = intersection(&candidates, &self.adj[w as usize]);
+        }
+        clique
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CliqueCover;
+    use crate::graph::Graph;
+
+    fn add_edge(graph: &mut Graph, u: u32, v: u32) {
+        graph.add_arc(u, v);
+        graph.add_arc(v, u);
+    }
+
+    #[test]
+    fn clique_cover_test_001() {
+        // the 4-clique 0, 1, 2, 3, the triangle 3, 4, 5 and the edge 5 - 6
+        let mut graph = Graph::new(7);
+        for (u, v) in [(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3), (3, 4), (3, 5), (4, 5), (5, 6)] {
+            add_edge(&mut graph, u, v);
+        }
+        let cliques = graph.clique_cover();
+        assert_eq!(cliques, vec![vec![0, 1, 2, 3], vec![3, 4, 5]]);
+    }
+}
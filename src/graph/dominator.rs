@@ -0,0 +1,223 @@
+//! Dominator trees computed with the Lengauer-Tarjan algorithm (the simple
+//! version with path compression). A vertex *v* dominates *w* with respect to
+//! a root *r* if every path from *r* to *w* passes through *v*. Splitting a
+//! vertex *x* into a source and a sink turns the question "which vertices lie
+//! on all cycles through *x*" into a dominator query.
+use super::Graph;
+
+const NONE: usize = usize::MAX;
+
+struct LengauerTarjan<'a> {
+    adj: &'a [Vec<u32>],
+    dfnum: Vec<usize>,
+    order: Vec<usize>,
+    parent: Vec<usize>,
+    semi: Vec<usize>,
+    ancestor: Vec<usize>,
+    label: Vec<usize>,
+    idom: Vec<usize>,
+    pred: Vec<Vec<usize>>,
+}
+
+impl<'a> LengauerTarjan<'a> {
+    fn new(adj: &'a [Vec<u32>]) -> LengauerTarjan<'a> {
+        let n = adj.len();
+        LengauerTarjan {
+            adj,
+            dfnum: vec![NONE; n],
+            order: Vec::new(),
+            parent: vec![NONE; n],
+            semi: vec![NONE; n],
+            ancestor: vec![NONE; n],
+            label: (0..n).collect(),
+            idom: vec![NONE; n],
+            pred: vec![Vec::new(); n],
+        }
+    }
+
+    /// Numbers the vertices reachable from `root` in DFS order, and records
+    /// the DFS tree and the predecessors of each reachable vertex.
+    fn dfs(&mut self, root: usize, skip: &[bool]) {
+        let mut stack = vec![(root, NONE)];
+        while let Some((vertex, parent)) = stack.pop() {
+            if parent != NONE {
+                self.pred[vertex].push(parent);
+            }
+            if self.dfnum[vertex] != NONE {
+                continue;
+            }
+            self.dfnum[vertex] = self.order.len();
+            self.semi[vertex] = self.order.len();
+            self.parent[vertex] = parent;
+            self.order.push(vertex);
+            for next in self.adj[vertex].iter().rev() {
+                if !skip[*next as usize] {
+                    stack.push((*next as usize, vertex));
+                }
+            }
+        }
+    }
+
+    fn compress(&mut self, vertex: usize) {
+        let mut path = Vec::new();
+        let mut current = vertex;
+        while self.ancestor[self.ancestor[current]] != NONE {
+            path.push(current);
+            current = self.ancestor[current];
+        }
+        while let Some(v) = path.pop() {
+            let a = self.ancestor[v];
+            if self.semi[self.label[a]] < self.semi[self.label[v]] {
+                self.label[v] = self.label[a];
+            }
+            self.ancestor[v] = self.ancestor[a];
+        }
+    }
+
+    fn eval(&mut self, vertex: usize) -> usize {
+        if self.ancestor[vertex] == NONE {
+            return vertex;
+        }
+        self.compress(vertex);
+        self.label[vertex]
+    }
+
+    fn run(mut self, root: usize, skip: &[bool]) -> Vec<Option<u32>> {
+        self.dfs(root, skip);
+        let mut bucket = vec![Vec::new(); self.adj.len()];
+        for i in (1..self.order.len()).rev() {
+            let w = self.order[i];
+            for j in 0..self.pred[w].len() {
+                let v = self.pred[w][j];
+                let u = self.eval(v);
+                if self.semi[u] < self.semi[w] {
+                    self.semi[w] = self.semi[u];
+                }
+            }
+            bucket[self.order[self.semi[w]]].push(w);
+
+            let p = self.parent[w];
+            self.ancestor[w] = p;
+            for v in std::mem::take(&mut bucket[p]) {
+                let u = self.eval(v);
+                self.idom[v] = if self.semi[u] < self.semi[v] { u } else { p };
+            }
+        }
+
+        for i in 1..self.order.len() {
+            let w = self.order[i];
+            if self.idom[w] != self.order[self.semi[w]] {
+                self.idom[w] = self.idom[self.idom[w]];
+            }
+        }
+
+        self.idom
+            .into_iter()
+            .enumerate()
+            .map(|(v, d)| if v == root || d == NONE { None } else { Some(d as u32) })
+            .collect()
+    }
+}
+
+pub trait Dominators {
+    /// Returns the immediate dominator of every vertex with respect to `root`,
+    /// or `None` for `root` and vertices unreachable from `root`.
+    fn dominator_tree(&self, root: u32) -> Vec<Option<u32>>;
+
+    /// Returns all vertices other than `vertex` that lie on every cycle
+    /// through `vertex`, ordered from `vertex` outwards along the dominator
+    /// tree. The result is empty if `vertex` is on no cycle.
+    fn cycle_dominators(&self, vertex: u32) -> Vec<u32>;
+}
+
+impl Dominators for Graph {
+    fn dominator_tree(&self, root: u32) -> Vec<Option<u32>> {
+        LengauerTarjan::new(&self.adj).run(root as usize, &self.deleted_vertices)
+    }
+
+    fn cycle_dominators(&self, vertex: u32) -> Vec<u32> {
+        // redirect all arcs entering `vertex` to a new sink
+        let sink = self.total_vertices() as u32;
+        let mut adj = self.adj.clone();
+        adj.push(Vec::new());
+        for source in &self.rev_adj[vertex as usize] {
+            let list = &mut adj[*source as usize];
+            if let Ok(index) = list.binary_search(&vertex) {
+                list[index] = sink;
+            }
+        }
+        let mut skip = self.deleted_vertices.clone();
+        skip.push(false);
+
+        let idom = LengauerTarjan::new(&adj).run(vertex as usize, &skip);
+        let mut dominators = Vec::new();
+        let mut current = idom[sink as usize];
+        while let Some(dominator) = current {
+            if dominator == vertex {
+                break;
+            }
+            dominators.push(dominator);
+            current = idom[dominator as usize];
+        }
+        dominators.reverse();
+        dominators
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Dominators;
+    use crate::graph::Graph;
+
+    #[test]
+    fn dominator_tree_test_001() {
+        // 0 -> {1, 2}, 1 -> 3, 2 -> 3, 3 -> 4
+        let mut graph = Graph::new(5);
+        graph.add_arc(0, 1);
+        graph.add_arc(0, 2);
+        graph.add_arc(1, 3);
+        graph.add_arc(2, 3);
+        graph.add_arc(3, 4);
+        let idom = graph.dominator_tree(0);
+        assert_eq!(idom, vec![None, Some(0), Some(0), Some(0), Some(3)]);
+    }
+
+    #[test]
+    fn dominator_tree_test_002() {
+        // back edge 4 -> 1 does not change the dominators
+        let mut graph = Graph::new(5);
+        graph.add_arc(0, 1);
+        graph.add_arc(1, 2);
+        graph.add_arc(2, 3);
+        graph.add_arc(1, 3);
+        graph.add_arc(3, 4);
+        graph.add_arc(4, 1);
+        let idom = graph.dominator_tree(0);
+        assert_eq!(idom, vec![None, Some(0), Some(1), Some(1), Some(3)]);
+    }
+
+    #[test]
+    fn cycle_dominators_test_001() {
+        // two cycles through 0, both passing 1 and 4
+        let mut graph = Graph::new(5);
+        graph.add_arc(0, 1);
+        graph.add_arc(1, 2);
+        graph.add_arc(1, 3);
+        graph.add_arc(2, 4);
+        graph.add_arc(3, 4);
+        graph.add_arc(4, 0);
+        assert_eq!(graph.cycle_dominators(0), vec![1, 4]);
+        assert_eq!(graph.cycle_dominators(2), vec![4, 0, 1]);
+    }
+
+    #[test]
+    fn cycle_dominators_test_002() {
+        let mut graph = Graph::new(3);
+        graph.add_arc(0, 1);
+        graph.add_arc(1, 0);
+        graph.add_arc(0, 2);
+        graph.add_arc(2, 0);
+        assert!(graph.cycle_dominators(0).is_empty());
+        assert_eq!(graph.cycle_dominators(1), vec![0]);
+    }
+}
@@ -0,0 +1,338 @@
+//! Maximum flow on the vertex-split graph. Every vertex *u* is split into
+//! *u_in* and *u_out* joined by an arc of capacity 1, so an integral flow
+//! corresponds to a set of vertex-disjoint paths. Used to compute the *petal
+//! number* of a vertex *v*: the maximum number of cycles through *v* that
+//! pairwise only share *v*, i.e., the maximum flow from *v_out* to *v_in*.
+use super::Graph;
+use std::collections::VecDeque;
+
+/// Residual network used by Dinic's algorithm. Arcs are stored in pairs, such
+/// that arc `e ^ 1` is the reverse of arc `e`.
+pub struct FlowNetwork {
+    /// The indices of the arcs leaving each node.
+    head: Vec<Vec<usize>>,
+
+    /// The node each arc points to.
+    target: Vec<usize>,
+
+    /// The residual capacity of each arc.
+    capacity: Vec<u32>,
+
+    /// The capacity of each arc before any flow was pushed.
+    original: Vec<u32>,
+
+    /// BFS-level of each node in the current phase.
+    level: Vec<i32>,
+
+    /// The next arc to try for each node in the current phase.
+    next_arc: Vec<usize>,
+
+    /// The nodes reached by the last BFS, so levels can be reset locally.
+    reached: Vec<usize>,
+
+    /// The arcs whose residual capacity differs from `original`.
+    dirty: Vec<usize>,
+}
+
+impl FlowNetwork {
+    pub fn new(nodes: usize) -> FlowNetwork {
+        FlowNetwork {
+            head: vec![Vec::new(); nodes],
+            target: Vec::new(),
+            capacity: Vec::new(),
+            original: Vec::new(),
+            level: vec![-1; nodes],
+            next_arc: vec![0; nodes],
+            reached: Vec::new(),
+            dirty: Vec::new(),
+        }
+    }
+
+    /// Builds the vertex-split network of `graph`. Vertex *u* is represented
+    /// by node `2u` (*u_in*) and node `2u + 1` (*u_out*).
+    pub fn vertex_split(graph: &Graph) -> FlowNetwork {
+        let mut network = FlowNetwork::new(2 * graph.total_vertices());
+        for u in 0..graph.total_vertices() {
+            if graph.deleted_vertices[u] {
+                continue;
+            }
+            network.add_arc(2 * u, 2 * u + 1, 1);
+            for w in &graph.adj[u] {
+                if *w as usize != u && !graph.deleted_vertices[*w as usize] {
+                    network.add_arc(2 * u + 1, 2 * *w as usize, 1);
+                }
+            }
+        }
+        network
+    }
+
+    pub fn add_arc(&mut self, from: usize, to: usize, capacity: u32) {
+        self.head[from].push(self.target.len());
+        self.target.push(to);
+        self.capacity.push(capacity);
+        self.original.push(capacity);
+
+        self.head[to].push(self.target.len());
+        self.target.push(from);
+        self.capacity.push(0);
+        self.original.push(0);
+    }
+
+    /// Removes all flow from the network. Only the arcs that carried flow are
+    /// restored, so a reset is cheap if the last flow was local.
+    pub fn reset(&mut self) {
+        for arc in self.dirty.drain(..) {
+            self.capacity[arc] = self.original[arc];
+            self.capacity[arc ^ 1] = self.original[arc ^ 1];
+        }
+    }
+
+    /// Returns the petal number of vertex `v` in a vertex-split network,
+    /// capped at `limit`. The flow of earlier calls is removed first.
+    pub fn petal_number(&mut self, v: usize, limit: usize) -> usize {
+        self.reset();
+        self.max_flow(2 * v + 1, 2 * v, limit)
+    }
+
+    /// Returns the flow currently sent over arc `arc`.
+    fn flow(&self, arc: usize) -> u32 {
+        self.original[arc].saturating_sub(self.capacity[arc])
+    }
+
+    /// Computes the levels of the nodes, but stops as soon as the sink is
+    /// reached since nodes further away never lie on a shortest path.
+    fn bfs(&mut self, source: usize, sink: usize) -> bool {
+        for node in self.reached.drain(..) {
+            self.level[node] = -1;
+            self.next_arc[node] = 0;
+        }
+        let mut queue = VecDeque::new();
+        self.level[source] = 0;
+        self.reached.push(source);
+        queue.push_back(source);
+        'bfs: while let Some(node) = queue.pop_front() {
+            for arc in &self.head[node] {
+                let next = self.target[*arc];
+                if self.capacity[*arc] > 0 && self.level[next] == -1 {
+                    self.level[next] = self.level[node] + 1;
+                    self.reached.push(next);
+                    if next == sink {
+                        break 'bfs;
+                    }
+                    queue.push_back(next);
+                }
+            }
+        }
+        self.level[sink] != -1
+    }
+
+    /// Finds a single augmenting path in the level graph and pushes one unit
+    /// of flow over it. Iterative to avoid deep recursion on long paths.
+    fn augment(&mut self, source: usize, sink: usize) -> bool {
+        let mut path: Vec<usize> = Vec::new();
+        let mut node = source;
+        loop {
+            if node == sink {
+                for arc in path {
+                    self.capacity[arc] -= 1;
+                    self.capacity[arc ^ 1] += 1;
+                    self.dirty.push(arc);
+                }
+                return true;
+            }
+
+            let mut advanced = false;
+            while self.next_arc[node] < self.head[node].len() {
+                let arc = self.head[node][self.next_arc[node]];
+                let next = self.target[arc];
+                if self.capacity[arc] > 0 && self.level[next] == self.level[node] + 1 {
+                    path.push(arc);
+                    node = next;
+                    advanced = true;
+                    break;
+                }
+                self.next_arc[node] += 1;
+            }
+
+            if !advanced {
+                // dead end, retreat and never try this node again in this phase
+                self.level[node] = -1;
+                match path.pop() {
+                    Some(arc) => {
+                        node = self.target[arc ^ 1];
+                        self.next_arc[node] += 1;
+                    }
+                    None => return false,
+                }
+            }
+        }
+    }
+
+    /// Computes the maximum flow from `source` to `sink`, but stops as soon as
+    /// the flow reaches `limit`. Since every vertex has capacity 1, each
+    /// augmenting path carries exactly one unit of flow.
+    pub fn max_flow(&mut self, source: usize, sink: usize, limit: usize) -> usize {
+        let mut flow = 0;
+        while flow < limit && self.bfs(source, sink) {
+            while flow < limit && self.augment(source, sink) {
+                flow += 1;
+            }
+        }
+        flow
+    }
+
+    /// Recovers the paths carrying flow from `source` to `sink` as sequences
+    /// of nodes, excluding `source` and `sink` themselves.
+    pub fn flow_paths(&self, source: usize, sink: usize) -> Vec<Vec<usize>> {
+        let mut used = vec![0; self.target.len()];
+        let mut paths = Vec::new();
+        for first in &self.head[source] {
+            if self.flow(*first) == 0 {
+                continue;
+            }
+            used[*first] += 1;
+            let mut path = Vec::new();
+            let mut node = self.target[*first];
+            while node != sink {
+                path.push(node);
+                let arc = self.head[node]
+                    .iter()
+                    .copied()
+                    .find(|arc| self.flow(*arc) > used[*arc])
+                    .unwrap();
+                used[arc] += 1;
+                node = self.target[arc];
+            }
+            paths.push(path);
+        }
+        paths
+    }
+
+    /// Returns which nodes can be reached from `source` in the residual
+    /// network, i.e., the source side of a minimum cut after a maximum flow.
+    pub fn residual_reachable(&self, source: usize) -> Vec<bool> {
+        let mut reachable = vec![false; self.head.len()];
+        let mut queue = VecDeque::new();
+        reachable[source] = true;
+        queue.push_back(source);
+        while let Some(node) = queue.pop_front() {
+            for arc in &self.head[node] {
+                let next = self.target[*arc];
+                if self.capacity[*arc] > 0 && !reachable[next] {
+                    reachable[next] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+        reachable
+    }
+}
+
+pub trait Petals {
+    /// Returns the petal number of `vertex`, capped at `limit`.
+    fn petal_number(&self, vertex: u32, limit: usize) -> usize;
+
+    /// Returns the petal number, capped at `limit`, of every vertex. Deleted
+    /// vertices have petal number 0.
+    fn petal_numbers(&self, limit: usize) -> Vec<usize>;
+
+    /// Returns a maximum set of cycles through `vertex` that pairwise only
+    /// share `vertex`.
+    fn petals(&self, vertex: u32) -> Vec<Vec<u32>>;
+}
+
+impl Petals for Graph {
+    fn petal_number(&self, vertex: u32, limit: usize) -> usize {
+        if self.deleted_vertices[vertex as usize] {
+            return 0;
+        }
+        FlowNetwork::vertex_split(self).petal_number(vertex as usize, limit)
+    }
+
+    fn petal_numbers(&self, limit: usize) -> Vec<usize> {
+        let mut network = FlowNetwork::vertex_split(self);
+        petal_numbers_in(self, &mut network, limit)
+    }
+
+    fn petals(&self, vertex: u32) -> Vec<Vec<u32>> {
+        if self.deleted_vertices[vertex as usize] {
+            return Vec::new();
+        }
+        let mut network = FlowNetwork::vertex_split(self);
+        let v = vertex as usize;
+        network.max_flow(2 * v + 1, 2 * v, usize::MAX);
+
+        let mut cycles = Vec::new();
+        for path in network.flow_paths(2 * v + 1, 2 * v) {
+            let mut cycle = vec![vertex];
+            // every vertex appears as u_in followed by u_out
+            for node in path.into_iter().step_by(2) {
+                cycle.push((node / 2) as u32);
+            }
+            cycles.push(cycle);
+        }
+        cycles
+    }
+}
+
+/// Returns the petal numbers of `graph`, capped at `limit`, in `network`,
+/// which has to be the vertex-split network of `graph`.
+fn petal_numbers_in(graph: &Graph, network: &mut FlowNetwork, limit: usize) -> Vec<usize> {
+    let mut petals = vec![0; graph.total_vertices()];
+    for (v, petal) in petals.iter_mut().enumerate() {
+        if graph.deleted_vertices[v] || graph.adj[v].is_empty() || graph.rev_adj[v].is_empty() {
+            continue;
+        }
+        *petal = network.petal_number(v, limit);
+    }
+    petals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Petals;
+    use crate::graph::Graph;
+
+    #[test]
+    fn petal_number_test_001() {
+        // three triangles sharing vertex 0
+        let mut graph = Graph::new(7);
+        for i in 0..3 {
+            let a = 2 * i + 1;
+            let b = 2 * i + 2;
+            graph.add_arc(0, a);
+            graph.add_arc(a, b);
+            graph.add_arc(b, 0);
+        }
+        assert_eq!(graph.petal_number(0, usize::MAX), 3);
+        assert_eq!(graph.petal_number(0, 2), 2);
+        assert_eq!(graph.petal_number(1, usize::MAX), 1);
+    }
+
+    #[test]
+    fn petal_number_test_002() {
+        // two cycles through 0 that also share vertex 1
+        let mut graph = Graph::new(4);
+        graph.add_arc(0, 1);
+        graph.add_arc(1, 2);
+        graph.add_arc(1, 3);
+        graph.add_arc(2, 0);
+        graph.add_arc(3, 0);
+        assert_eq!(graph.petal_number(0, usize::MAX), 1);
+        assert_eq!(graph.petal_numbers(usize::MAX), vec![1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn petals_test_001() {
+        let mut graph = Graph::new(5);
+        graph.add_arc(0, 1);
+        graph.add_arc(1, 0);
+        graph.add_arc(0, 2);
+        graph.add_arc(2, 3);
+        graph.add_arc(3, 0);
+        graph.add_arc(4, 0);
+        let mut petals = graph.petals(0);
+        petals.sort();
+        assert_eq!(petals, vec![vec![0, 1], vec![0, 2, 3]]);
+    }
+}
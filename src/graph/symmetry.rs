@@ -0,0 +1,195 @@
+//! Automorphisms found by colour refinement with individualisation, a compact
+//! version of the search of nauty. Two vertices are only put into the same
+//! orbit through an automorphism that has been verified, so the orbits are
+//! those of a subgroup of the automorphism group. Symmetry arguments on them
+//! stay valid if the search gives up early.
+use super::Graph;
+
+/// Number of nodes of the search for an automorphism that maps one vertex to
+/// another, after which the vertices are considered to be in different
+/// orbits.
+const SEARCH_LIMIT: usize = 256;
+
+pub trait Symmetry {
+    /// Returns the orbits of at least two active vertices.
+    fn orbits(&self) -> Vec<Vec<u32>>;
+}
+
+impl Symmetry for Graph {
+    fn orbits(&self) -> Vec<Vec<u32>> {
+        let active = self.get_active_vertices();
+        let mut colors = vec![0; self.total_vertices()];
+        self.refine(&mut colors, &active);
+
+        let mut parent: Vec<u32> = (0..self.total_vertices() as u32).collect();
+        for cell in cells(&colors, &active) {
+            // one vertex of every orbit of the cell found so far
+            let mut representatives: Vec<u32> = Vec::new();
+            for v in cell {
+                for u in &representatives {
+                    if find(&mut parent, *u) == find(&mut parent, v) {
+                        break;
+                    }
+                    let mut budget = SEARCH_LIMIT;
+                    let a = self.individualize(&colors, *u, &active);
+                    let b = self.individualize(&colors, v, &active);
+                    if let Some(map) = self.automorphism(a, b, &active, &mut budget) {
+                        for x in &active {
+                            let (rx, ry) = (find(&mut parent, *x), find(&mut parent, map[*x as usize]));
+                            parent[rx as usize] = ry;
+                        }
+                        break;
+                    }
+                }
+                if representatives.iter().all(|u| find(&mut parent, *u) != find(&mut parent, v)) {
+                    representatives.push(v);
+                }
+            }
+        }
+
+        let mut orbits = vec![Vec::new(); self.total_vertices()];
+        for v in &active {
+            let root = find(&mut parent, *v);
+            orbits[root as usize].push(*v);
+        }
+        let mut orbits: Vec<_> = orbits.into_iter().filter(|orbit| orbit.len() > 1).collect();
+        orbits.sort_unstable();
+        orbits
+    }
+}
+
+impl Graph {
+    /// Refines `colors` until any two active vertices of the same colour have
+    /// the same number of out- and in-neighbours of every colour. The new
+    /// colours only depend on the old colours and the adjacency, so the
+    /// refinements of two colourings can be compared.
+    fn refine(&self, colors: &mut [usize], active: &[u32]) {
+        let mut count = cells(colors, active).len();
+        loop {
+            let mut signatures: Vec<_> = active
+                .iter()
+                .map(|v| {
+                    let mut outgoing: Vec<_> = self.adj[*v as usize].iter().map(|u| colors[*u as usize]).collect();
+                    let mut incoming: Vec<_> = self.rev_adj[*v as usize].iter().map(|u| colors[*u as usize]).collect();
+                    outgoing.sort_unstable();
+                    incoming.sort_unstable();
+                    let mut signature = vec![colors[*v as usize], outgoing.len()];
+                    signature.append(&mut outgoing);
+                    signature.append(&mut incoming);
+                    (signature, *v)
+                })
+                .collect();
+            signatures.sort_unstable();
+
+            let mut color = 0;
+            for i in 0..signatures.len() {
+                if i > 0 && signatures[i].0 != signatures[i - 1].0 {
+                    color += 1;
+                }
+                colors[signatures[i].1 as usize] = color;
+            }
+            if color + 1 == count {
+                return;
+            }
+            count = color + 1;
+        }
+    }
+
+    /// Returns the refinement of `colors` in which `vertex` has a colour of
+    /// its own.
+    fn individualize(&self, colors: &[usize], vertex: u32, active: &[u32]) -> Vec<usize> {
+        let mut colors = colors.to_vec();
+        colors[vertex as usize] = active.len();
+        self.refine(&mut colors, active);
+        colors
+    }
+
+    /// Searches an automorphism that maps every vertex of colour *c* in `a`
+    /// to a vertex of colour *c* in `b`, and returns it as a map of the
+    /// vertices. Gives up once `budget` is used up.
+    fn automorphism(&self, a: Vec<usize>, b: Vec<usize>, active: &[u32], budget: &mut usize) -> Option<Vec<u32>> {
+        if *budget == 0 {
+            return None;
+        }
+        *budget -= 1;
+
+        let cells_a = cells(&a, active);
+        let cells_b = cells(&b, active);
+        if cells_a.len() != cells_b.len() || cells_a.iter().zip(&cells_b).any(|(x, y)| x.len() != y.len()) {
+            return None;
+        }
+
+        let cell = match cells_a.iter().position(|cell| cell.len() > 1) {
+            Some(cell) => cell,
+            None => {
+                let mut map: Vec<u32> = (0..self.total_vertices() as u32).collect();
+                for (x, y) in cells_a.iter().zip(&cells_b) {
+                    map[x[0] as usize] = y[0];
+                }
+                return self.is_automorphism(&map, active).then_some(map);
+            }
+        };
+
+        let a = self.individualize(&a, cells_a[cell][0], active);
+        for y in &cells_b[cell] {
+            let b = self.individualize(&b, *y, active);
+            if let Some(map) = self.automorphism(a.clone(), b, active, budget) {
+                return Some(map);
+            }
+            if *budget == 0 {
+                return None;
+            }
+        }
+        None
+    }
+
+    /// Returns whether the bijection `map` of the active vertices preserves
+    /// all arcs. Refinement ensures equal degrees, so no arc is missing.
+    fn is_automorphism(&self, map: &[u32], active: &[u32]) -> bool {
+        active.iter().all(|u| {
+            let image = &self.adj[map[*u as usize] as usize];
+            self.adj[*u as usize]
+                .iter()
+                .all(|v| image.binary_search(&map[*v as usize]).is_ok())
+        })
+    }
+}
+
+/// Returns the active vertices grouped by their colour.
+fn cells(colors: &[usize], active: &[u32]) -> Vec<Vec<u32>> {
+    let colors_used = active.iter().map(|v| colors[*v as usize] + 1).max().unwrap_or(0);
+    let mut cells = vec![Vec::new(); colors_used];
+    for v in active {
+        cells[colors[*v as usize]].push(*v);
+    }
+    cells.retain(|cell| !cell.is_empty());
+    cells
+}
+
+fn find(parent: &mut [u32], mut vertex: u32) -> u32 {
+    while parent[vertex as usize] != vertex {
+        parent[vertex as usize] = parent[parent[vertex as usize] as usize];
+        vertex = parent[vertex as usize];
+    }
+    vertex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Symmetry;
+    use crate::graph::Graph;
+
+    #[test]
+    fn orbits_test_001() {
+        // two directed triangles and a 2-cycle
+        let mut graph = Graph::new(8);
+        for (u, v) in [(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3), (6, 7), (7, 6)] {
+            graph.add_arc(u, v);
+        }
+        assert_eq!(graph.orbits(), vec![vec![0, 1, 2, 3, 4, 5], vec![6, 7]]);
+
+        // an extra arc out of 0 breaks the symmetry of its triangle
+        graph.add_arc(0, 6);
+        assert_eq!(graph.orbits(), vec![vec![3, 4, 5]]);
+    }
+}
@@ -0,0 +1,276 @@
+//! Vertex cover reductions on the undirected graph produced by the split
+//! reduction. After the split, a DFVS consists of a vertex cover of the
+//! undirected graph that is also a DFVS of the directed graph. For vertices
+//! that have no arcs left in the directed graph, only the vertex cover part
+//! matters, so the classical vertex cover rules remain sound as long as every
+//! vertex that is excluded or folded is such an *undirected-only* vertex.
+use super::{flow::FlowNetwork, Graph};
+use crate::util::algorithms::difference;
+
+/// The degree-2 folds applied to the undirected graph, in order of
+/// application. A fold of `vertex` with neighbours `a` and `b` merges `vertex`,
+/// `a` and `b` into `a`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Folds {
+    folds: Vec<(u32, u32, u32)>,
+}
+
+impl Folds {
+    pub fn from_folds(folds: Vec<(u32, u32, u32)>) -> Folds {
+        Folds { folds }
+    }
+
+    pub fn folds(&self) -> &[(u32, u32, u32)] {
+        &self.folds
+    }
+
+    pub fn len(&self) -> usize {
+        self.folds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.folds.is_empty()
+    }
+
+    /// Lifts a solution of the folded graph to a solution of the graph before
+    /// folding. Each fold adds exactly one vertex: both `a` and `b` if the
+    /// merged vertex `a` is in the solution, `vertex` otherwise.
+    pub fn lift(&self, mut solution: Vec<u32>) -> Vec<u32> {
+        for (vertex, a, b) in self.folds.iter().rev() {
+            if solution.contains(a) {
+                solution.push(*b);
+            } else {
+                solution.push(*vertex);
+            }
+        }
+        solution
+    }
+}
+
+pub trait UndirectedReduce {
+    /// Applies the degree-1, domination, degree-2 folding and crown rules to
+    /// the undirected-only vertices of `self`, the undirected graph of the
+    /// split, where `directed` is the directed graph of the split. Returns
+    /// the forced vertices, which are removed from both graphs.
+    fn vertex_cover_reduction(&mut self, directed: &mut Graph, folds: &mut Folds) -> Vec<u32>;
+}
+
+impl UndirectedReduce for Graph {
+    fn vertex_cover_reduction(&mut self, directed: &mut Graph, folds: &mut Folds) -> Vec<u32> {
+        let mut forced = Vec::new();
+        loop {
+            let mut reduced = false;
+            for v in 0..self.total_vertices() {
+                if self.deleted_vertices[v]
+                    || self.adj[v].is_empty()
+                    || !is_undirected_only(directed, v as u32)
+                {
+                    continue;
+                }
+
+                if let Some(u) = self.dominating_neighbor(v as u32) {
+                    self.remove_vertex(u);
+                    directed.remove_vertex(u);
+                    forced.push(u);
+                    reduced = true;
+                } else if self.adj[v].len() == 2 && self.fold(v as u32, directed, folds) {
+                    reduced = true;
+                }
+            }
+
+            if !reduced {
+                let mut crown = self.crown(directed);
+                if crown.is_empty() {
+                    break;
+                }
+                self.remove_vertices(&crown);
+                directed.remove_vertices(&crown);
+                forced.append(&mut crown);
+            }
+        }
+        forced
+    }
+}
+
+fn is_undirected_only(directed: &Graph, vertex: u32) -> bool {
+    directed.adj[vertex as usize].is_empty() && directed.rev_adj[vertex as usize].is_empty()
+}
+
+impl Graph {
+    /// Returns a neighbour *u* of `vertex` with *N[vertex]* ⊆ *N[u]*, which
+    /// includes the only neighbour of a degree-1 vertex.
+    fn dominating_neighbor(&self, vertex: u32) -> Option<u32> {
+        let neighbors = &self.adj[vertex as usize];
+        if neighbors.len() == 1 {
+            return Some(neighbors[0]);
+        }
+        for u in neighbors {
+            let mut closed = self.adj[*u as usize].clone();
+            closed.push(*u);
+            closed.sort_unstable();
+            if difference(neighbors, &closed).is_empty() {
+                return Some(*u);
+            }
+        }
+        None
+    }
+
+    /// Folds the degree-2 `vertex` with its neighbours *a* and *b* into *a*,
+    /// provided *a* and *b* are undirected-only and not adjacent.
+    fn fold(&mut self, vertex: u32, directed: &Graph, folds: &mut Folds) -> bool {
+        let a = self.adj[vertex as usize][0];
+        let b = self.adj[vertex as usize][1];
+        if !is_undirected_only(directed, a)
+            || !is_undirected_only(directed, b)
+            || self.adj[a as usize].binary_search(&b).is_ok()
+        {
+            return false;
+        }
+
+        self.remove_vertex(vertex);
+        let neighbors = self.adj[b as usize].clone();
+        self.remove_vertex(b);
+        for u in neighbors {
+            self.add_arc(a, u);
+            self.add_arc(u, a);
+        }
+        folds.folds.push((vertex, a, b));
+        true
+    }
+
+    /// Finds a crown *(I, H)*: an independent set *I* of undirected-only
+    /// vertices and its neighbourhood *H*, such that *H* can be matched into
+    /// *I*. Then there is an optimal solution containing *H* and avoiding *I*,
+    /// and *H* is returned. *I* is taken from the vertices with value 0 in a
+    /// half-integral optimum of the vertex cover LP (Nemhauser-Trotter),
+    /// computed via a maximum matching in the bipartite double cover.
+    fn crown(&self, directed: &Graph) -> Vec<u32> {
+        let n = self.total_vertices();
+        let (source, sink) = (2 * n, 2 * n + 1);
+        let mut network = FlowNetwork::new(2 * n + 2);
+        for v in 0..n {
+            if self.deleted_vertices[v] || self.adj[v].is_empty() {
+                continue;
+            }
+            network.add_arc(source, 2 * v, 1);
+            network.add_arc(2 * v + 1, sink, 1);
+            for u in &self.adj[v] {
+                network.add_arc(2 * v, 2 * *u as usize + 1, 1);
+            }
+        }
+        network.max_flow(source, sink, usize::MAX);
+        let reachable = network.residual_reachable(source);
+
+        let mut independent = vec![false; n];
+        for v in 0..n {
+            if !self.deleted_vertices[v]
+                && !self.adj[v].is_empty()
+                && reachable[2 * v]
+                && !reachable[2 * v + 1]
+                && is_undirected_only(directed, v as u32)
+            {
+                independent[v] = true;
+            }
+        }
+
+        // Shrink I until its neighbourhood can be matched into it.
+        loop {
+            let mut head = Vec::new();
+            for (v, list) in self.adj.iter().enumerate() {
+                if independent[v] {
+                    head.extend_from_slice(list);
+                }
+            }
+            head.sort_unstable();
+            head.dedup();
+            if head.is_empty() {
+                return head;
+            }
+
+            let mut matching = FlowNetwork::new(2 * n + 2);
+            for h in &head {
+                matching.add_arc(source, 2 * *h as usize, 1);
+                for v in &self.adj[*h as usize] {
+                    if independent[*v as usize] {
+                        matching.add_arc(2 * *h as usize, 2 * *v as usize + 1, 1);
+                    }
+                }
+            }
+            for (v, member) in independent.iter().enumerate() {
+                if *member {
+                    matching.add_arc(2 * v + 1, sink, 1);
+                }
+            }
+            if matching.max_flow(source, sink, usize::MAX) == head.len() {
+                return head;
+            }
+
+            let mut matched = vec![false; n];
+            for path in matching.flow_paths(source, sink) {
+                matched[path[0] / 2] = true;
+            }
+            for h in &head {
+                if !matched[*h as usize] {
+                    for v in &self.adj[*h as usize] {
+                        independent[*v as usize] = false;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Folds, UndirectedReduce};
+    use crate::graph::Graph;
+
+    fn undirected(vertices: usize, edges: &[(u32, u32)]) -> Graph {
+        let mut graph = Graph::new(vertices);
+        for (u, v) in edges {
+            graph.add_arc(*u, *v);
+            graph.add_arc(*v, *u);
+        }
+        graph
+    }
+
+    #[test]
+    fn vertex_cover_reduction_test_001() {
+        // a star is solved by taking its center
+        let mut graph = undirected(4, &[(0, 1), (0, 2), (0, 3)]);
+        let mut directed = Graph::new(4);
+        let mut folds = Folds::default();
+        let forced = graph.vertex_cover_reduction(&mut directed, &mut folds);
+        assert_eq!(forced, vec![0]);
+        assert!(folds.is_empty());
+    }
+
+    #[test]
+    fn vertex_cover_reduction_test_002() {
+        // the cycle on 5 vertices needs 3 vertices, one of which comes from a fold
+        let edges = [(0, 1), (1, 2), (2, 3), (3, 4), (4, 0)];
+        let original = undirected(5, &edges);
+        let mut graph = original.clone();
+        let mut directed = Graph::new(5);
+        let mut folds = Folds::default();
+        let forced = graph.vertex_cover_reduction(&mut directed, &mut folds);
+        assert!(!folds.is_empty());
+        assert_eq!(forced.len() + folds.len(), 3);
+        let solution = folds.lift(forced);
+        assert_eq!(solution.len(), 3);
+        assert!(original.is_acyclic_with_fvs(&solution));
+    }
+
+    #[test]
+    fn vertex_cover_reduction_test_003() {
+        // vertex 1 has a directed arc, so it cannot be excluded by a fold
+        let mut graph = undirected(4, &[(0, 1), (0, 2)]);
+        let mut directed = Graph::new(4);
+        directed.add_arc(1, 3);
+        directed.add_arc(3, 1);
+        let mut folds = Folds::default();
+        let forced = graph.vertex_cover_reduction(&mut directed, &mut folds);
+        assert!(folds.is_empty());
+        assert_eq!(forced, vec![0]);
+    }
+}
@@ -0,0 +1,124 @@
+//! Separation of cycle inequalities for fractional solutions. Given a value
+//! *x(v)* for every vertex, a cycle *C* violates its inequality if the sum of
+//! *x* over *C* is below 1. The lightest cycle through a vertex is found with
+//! Dijkstra's algorithm, where entering a vertex costs its value.
+use super::Graph;
+use crate::util::{Heap, KeyValue, MinItem};
+
+/// Vertex weights are scaled to integers for the heap.
+const SCALE: f64 = 1e6;
+
+pub trait WeightedCycles {
+    /// Returns cycles whose total weight under `weights` is below `limit`.
+    /// Every cycle is the lightest one through a vertex that is not on any
+    /// earlier cycle.
+    fn light_cycles(&self, weights: &[f64], limit: f64) -> Vec<Vec<u32>>;
+}
+
+impl WeightedCycles for Graph {
+    fn light_cycles(&self, weights: &[f64], limit: f64) -> Vec<Vec<u32>> {
+        let n = self.total_vertices();
+        let weight: Vec<i64> = weights
+            .iter()
+            .map(|w| (w.max(0.) * SCALE).round() as i64)
+            .collect();
+        let limit = (limit * SCALE).floor() as i64;
+
+        let mut covered = vec![false; n];
+        let mut cycles = Vec::new();
+        for source in 0..n {
+            if covered[source] || self.adj[source].is_empty() || weight[source] >= limit {
+                continue;
+            }
+            if let Some(cycle) = self.lightest_cycle(source as u32, &weight, limit) {
+                for v in &cycle {
+                    covered[*v as usize] = true;
+                }
+                cycles.push(cycle);
+            }
+        }
+        cycles
+    }
+}
+
+impl Graph {
+    /// Returns the lightest cycle through `source` if it weighs less than
+    /// `limit`.
+    fn lightest_cycle(&self, source: u32, weight: &[i64], limit: i64) -> Option<Vec<u32>> {
+        let n = self.total_vertices();
+        let mut distance = vec![i64::MAX; n];
+        let mut parent = vec![source; n];
+        let mut done = vec![false; n];
+        let mut heap = Heap::new(n);
+
+        distance[source as usize] = weight[source as usize];
+        heap.insert(MinItem::new(source as usize, distance[source as usize]));
+        let mut best = limit;
+        let mut last = None;
+        while let Some(item) = heap.extract_min() {
+            let u = item.key();
+            if item.value() >= best {
+                break;
+            }
+            done[u] = true;
+            for v in &self.adj[u] {
+                let v = *v as usize;
+                if v == source as usize {
+                    // closing the cycle adds no weight, the source is counted
+                    if distance[u] < best {
+                        best = distance[u];
+                        last = Some(u as u32);
+                    }
+                    continue;
+                }
+                if done[v] {
+                    continue;
+                }
+                let candidate = distance[u] + weight[v];
+                if candidate < distance[v] {
+                    if distance[v] == i64::MAX {
+                        heap.insert(MinItem::new(v, candidate));
+                    } else {
+                        heap.decrease_key(MinItem::new(v, candidate));
+                    }
+                    distance[v] = candidate;
+                    parent[v] = u as u32;
+                }
+            }
+        }
+
+        let mut vertex = last?;
+        let mut cycle = vec![vertex];
+        while vertex != source {
+            vertex = parent[vertex as usize];
+            cycle.push(vertex);
+        }
+        cycle.reverse();
+        Some(cycle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WeightedCycles;
+    use crate::graph::Graph;
+
+    #[test]
+    fn light_cycles_test_001() {
+        // the triangle 0 -> 1 -> 2 -> 0 and the 2-cycle 2 <-> 3
+        let mut graph = Graph::new(4);
+        graph.add_arc(0, 1);
+        graph.add_arc(1, 2);
+        graph.add_arc(2, 0);
+        graph.add_arc(2, 3);
+        graph.add_arc(3, 2);
+
+        // only the triangle is violated
+        let cycles = graph.light_cycles(&[0.2, 0.2, 0.5, 0.5], 1.);
+        assert_eq!(cycles, vec![vec![0, 1, 2]]);
+
+        // no cycle is violated
+        let cycles = graph.light_cycles(&[0.5, 0.5, 1., 0.], 1.);
+        assert!(cycles.is_empty());
+    }
+}
@@ -5,7 +5,7 @@ use crate::{
     util::Constraint,
 };
 
-use super::{Heuristic, hitting_set_upper_bound};
+use super::{AnnealingOracle, DisjointCycles, Heuristic, ImplicitHittingSet};
 
 pub struct HittingSetDFVS {}
 impl Heuristic for HittingSetDFVS {
@@ -69,14 +69,9 @@ impl Heuristic for HittingSetDFVS {
             hitting_set.push(Constraint::new(cycle, 1));
         }
 
-        let mut upper_bound = hitting_set_upper_bound(&hitting_set, vertices);
-        while !graph.is_acyclic_with_fvs(&upper_bound) {
-            let cycles = graph.disjoint_edge_cycle_cover(&upper_bound);
-            for cycle in cycles {
-                hitting_set.push(Constraint::new(cycle, 1));
-            }
-            upper_bound = hitting_set_upper_bound(&hitting_set, vertices);
-        }
+        let oracle = AnnealingOracle::new(vertices);
+        let mut ihs = ImplicitHittingSet::new(oracle, Box::new(DisjointCycles {}), hitting_set, vertices);
+        let mut upper_bound = ihs.solve(&graph, |_| {});
         upper_bound.append(&mut forced);
         upper_bound.append(&mut initial);
         upper_bound
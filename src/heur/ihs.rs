@@ -0,0 +1,315 @@
+//! The implicit hitting set loop shared by the ILP, the split reduction and
+//! the hitting set heuristic. Every cycle of the graph has to be hit by a
+//! DFVS, so a hitting set of some cycles is computed, and cycles it misses are
+//! added until it hits all of them. The hitting sets come from an oracle and
+//! the missed cycles, the cores, from an extractor.
+use super::{hitting_set_upper_bound, make_minimal, restrict_solution};
+use crate::{
+    graph::{EdgeCycleCover, Graph},
+    io::Cores,
+    util::{minimum_hitting_set, packing_lower_bound, Constraint},
+};
+use std::collections::VecDeque;
+
+pub trait HittingSetOracle {
+    /// Adds constraints that the next hitting sets have to hit.
+    fn add(&mut self, constraints: &[Constraint]);
+
+    /// Returns a hitting set of all constraints added so far.
+    fn hit(&mut self) -> Vec<u32>;
+
+    /// Returns whether the hitting sets are minimum, which makes their size a
+    /// lower bound of the DFVS.
+    fn is_exact(&self) -> bool;
+}
+
+/// Oracle of the simulated annealing heuristic for hitting sets.
+pub struct AnnealingOracle {
+    constraints: Vec<Constraint>,
+    vertices: usize,
+}
+
+impl AnnealingOracle {
+    pub fn new(vertices: usize) -> AnnealingOracle {
+        AnnealingOracle {
+            constraints: Vec::new(),
+            vertices,
+        }
+    }
+}
+
+impl HittingSetOracle for AnnealingOracle {
+    fn add(&mut self, constraints: &[Constraint]) {
+        self.constraints.extend_from_slice(constraints);
+    }
+
+    fn hit(&mut self) -> Vec<u32> {
+        if self.constraints.is_empty() {
+            return Vec::new();
+        }
+        hitting_set_upper_bound(&self.constraints, self.vertices)
+    }
+
+    fn is_exact(&self) -> bool {
+        false
+    }
+}
+
+/// Oracle of the branch and bound for hitting sets, started from the
+/// simulated annealing solution.
+pub struct BranchAndBoundOracle {
+    constraints: Vec<Constraint>,
+    vertices: usize,
+}
+
+impl BranchAndBoundOracle {
+    pub fn new(vertices: usize) -> BranchAndBoundOracle {
+        BranchAndBoundOracle {
+            constraints: Vec::new(),
+            vertices,
+        }
+    }
+}
+
+impl HittingSetOracle for BranchAndBoundOracle {
+    fn add(&mut self, constraints: &[Constraint]) {
+        self.constraints.extend_from_slice(constraints);
+    }
+
+    fn hit(&mut self) -> Vec<u32> {
+        if self.constraints.is_empty() {
+            return Vec::new();
+        }
+        let upper_bound = hitting_set_upper_bound(&self.constraints, self.vertices);
+        minimum_hitting_set(&self.constraints, self.vertices, upper_bound)
+    }
+
+    fn is_exact(&self) -> bool {
+        true
+    }
+}
+
+pub trait CoreExtractor {
+    /// Returns cycles of `graph` that avoid `hitting_set`, which is not a
+    /// DFVS of `graph`.
+    fn cores(&self, graph: &Graph, hitting_set: &[u32]) -> Vec<Vec<u32>>;
+}
+
+pub fn extractor(cores: &Cores) -> Box<dyn CoreExtractor> {
+    match cores {
+        Cores::Disjoint => Box::new(DisjointCycles {}),
+        Cores::Shortest => Box::new(ShortestCycles {}),
+        Cores::Minimal => Box::new(MinimalCycles {}),
+    }
+}
+
+/// A shortest cycle through every arc, as far as the cycles are distinct.
+pub struct DisjointCycles {}
+impl CoreExtractor for DisjointCycles {
+    fn cores(&self, graph: &Graph, hitting_set: &[u32]) -> Vec<Vec<u32>> {
+        graph.disjoint_edge_cycle_cover(hitting_set)
+    }
+}
+
+/// Vertex disjoint cycles, each a shortest cycle of the graph without the
+/// hitting set and the cycles before it.
+pub struct ShortestCycles {}
+impl CoreExtractor for ShortestCycles {
+    fn cores(&self, graph: &Graph, hitting_set: &[u32]) -> Vec<Vec<u32>> {
+        let mut removed = vec![false; graph.total_vertices()];
+        for v in hitting_set {
+            removed[*v as usize] = true;
+        }
+        let mut cycles = Vec::new();
+        while let Some(cycle) = shortest_cycle(graph, &removed) {
+            for v in &cycle {
+                removed[*v as usize] = true;
+            }
+            cycles.push(cycle);
+        }
+        cycles
+    }
+}
+
+/// The hitting set is completed to a minimal DFVS, and every vertex of it
+/// that is not in the hitting set gives a cycle on which it is the only
+/// vertex of the DFVS.
+pub struct MinimalCycles {}
+impl CoreExtractor for MinimalCycles {
+    fn cores(&self, graph: &Graph, hitting_set: &[u32]) -> Vec<Vec<u32>> {
+        let complete = restrict_solution(graph, hitting_set);
+        let minimal = make_minimal(&mut graph.clone(), complete);
+        graph
+            .find_cycle_from_minimal(&minimal)
+            .into_iter()
+            .filter(|cycle| cycle.iter().all(|v| !hitting_set.contains(v)))
+            .collect()
+    }
+}
+
+/// Returns a shortest cycle of the vertices of `graph` that are not
+/// `removed`, by a breadth-first search from every vertex.
+fn shortest_cycle(graph: &Graph, removed: &[bool]) -> Option<Vec<u32>> {
+    let mut best: Option<Vec<u32>> = None;
+    for source in graph.get_active_vertices() {
+        if removed[source as usize] {
+            continue;
+        }
+        let mut discovered = vec![None; graph.total_vertices()];
+        let mut queue = VecDeque::from([(source, 1)]);
+        'search: while let Some((vertex, length)) = queue.pop_front() {
+            if best.as_ref().is_some_and(|best| length >= best.len()) {
+                break;
+            }
+            for next in graph.get_outgoing(&vertex) {
+                if removed[*next as usize] {
+                    continue;
+                }
+                if *next == source {
+                    let mut cycle = vec![vertex];
+                    while let Some(previous) = discovered[*cycle.last().unwrap() as usize] {
+                        cycle.push(previous);
+                    }
+                    best = Some(cycle);
+                    break 'search;
+                }
+                if discovered[*next as usize].is_none() && *next != source {
+                    discovered[*next as usize] = Some(vertex);
+                    queue.push_back((*next, length + 1));
+                }
+            }
+        }
+    }
+    best
+}
+
+pub struct ImplicitHittingSet<O: HittingSetOracle> {
+    oracle: O,
+    extractor: Box<dyn CoreExtractor>,
+
+    /// All constraints given to the oracle.
+    constraints: Vec<Constraint>,
+
+    /// A known DFVS, which is optimal once it meets the lower bound.
+    upper_bound: Option<Vec<u32>>,
+    lower_bound: usize,
+    iterations: usize,
+    vertices: usize,
+}
+
+impl<O: HittingSetOracle> ImplicitHittingSet<O> {
+    pub fn new(
+        mut oracle: O,
+        extractor: Box<dyn CoreExtractor>,
+        constraints: Vec<Constraint>,
+        vertices: usize,
+    ) -> ImplicitHittingSet<O> {
+        oracle.add(&constraints);
+        ImplicitHittingSet::with_added(oracle, extractor, constraints, vertices)
+    }
+
+    /// Same as [`ImplicitHittingSet::new`], but for an oracle that already
+    /// has to hit `constraints`.
+    pub fn with_added(
+        oracle: O,
+        extractor: Box<dyn CoreExtractor>,
+        constraints: Vec<Constraint>,
+        vertices: usize,
+    ) -> ImplicitHittingSet<O> {
+        ImplicitHittingSet {
+            oracle,
+            extractor,
+            constraints,
+            upper_bound: None,
+            lower_bound: 0,
+            iterations: 0,
+            vertices,
+        }
+    }
+
+    /// Sets a DFVS that also hits the constraints, which is returned if the
+    /// lower bound shows it to be optimal.
+    pub fn with_upper_bound(mut self, upper_bound: Vec<u32>) -> ImplicitHittingSet<O> {
+        self.upper_bound = Some(upper_bound);
+        self
+    }
+
+    /// Returns a hitting set of the constraints that is a DFVS of `graph`,
+    /// which is minimum if the oracle is exact. `progress` is called with
+    /// the constraints whenever cores are added.
+    pub fn solve(&mut self, graph: &Graph, mut progress: impl FnMut(&[Constraint])) -> Vec<u32> {
+        loop {
+            self.iterations += 1;
+            let hitting_set = self.oracle.hit();
+
+            // a hitting set that is not minimum only bounds the DFVS from
+            // above, so the bound comes from a packing of the constraints
+            let lower_bound = if self.oracle.is_exact() {
+                hitting_set.len()
+            } else {
+                packing_lower_bound(&self.constraints, self.vertices)
+            };
+            self.lower_bound = self.lower_bound.max(lower_bound);
+
+            if let Some(upper_bound) = &self.upper_bound {
+                if upper_bound.len() <= self.lower_bound {
+                    return upper_bound.clone();
+                }
+            }
+            if graph.is_acyclic_with_fvs(&hitting_set) {
+                return hitting_set;
+            }
+
+            let mut cores = self.extractor.cores(graph, &hitting_set);
+            if cores.is_empty() {
+                cores.push(graph.find_cycle_with_fvs(&hitting_set).unwrap());
+            }
+            let cores: Vec<_> = cores.into_iter().map(|core| Constraint::new(core, 1)).collect();
+            self.oracle.add(&cores);
+            self.constraints.extend(cores);
+            progress(&self.constraints);
+        }
+    }
+
+    pub fn oracle(&self) -> &O {
+        &self.oracle
+    }
+
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    pub fn into_constraints(self) -> Vec<Constraint> {
+        self.constraints
+    }
+
+    /// Returns the best lower bound on the size of a DFVS found so far.
+    pub fn lower_bound(&self) -> usize {
+        self.lower_bound
+    }
+
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BranchAndBoundOracle, ImplicitHittingSet, ShortestCycles};
+    use crate::graph::Graph;
+
+    #[test]
+    fn implicit_hitting_set_test_001() {
+        // two triangles sharing vertex 0 and a 2-cycle
+        let mut graph = Graph::new(7);
+        for (u, v) in [(0, 1), (1, 2), (2, 0), (0, 3), (3, 4), (4, 0), (5, 6), (6, 5)] {
+            graph.add_arc(u, v);
+        }
+        let oracle = BranchAndBoundOracle::new(7);
+        let mut ihs = ImplicitHittingSet::new(oracle, Box::new(ShortestCycles {}), Vec::new(), 7);
+        let solution = ihs.solve(&graph, |_| {});
+        assert_eq!(solution.len(), 2);
+        assert!(graph.is_acyclic_with_fvs(&solution));
+        assert_eq!(ihs.lower_bound(), 2);
+    }
+}
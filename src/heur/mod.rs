@@ -2,10 +2,14 @@ use crate::graph::{Graph, HeuristicReduce};
 
 mod hs_sa;
 mod hsheur;
+mod ihs;
 mod sa;
 pub use hs_sa::hitting_set_upper_bound;
 pub use hs_sa::hitting_set_upper_bound_custom;
 pub use hsheur::HittingSetDFVS;
+pub use ihs::{
+    extractor, AnnealingOracle, BranchAndBoundOracle, DisjointCycles, HittingSetOracle, ImplicitHittingSet,
+};
 use rustc_hash::FxHashSet;
 pub use sa::SimulatedAnnealing;
 pub trait Heuristic {
@@ -66,6 +70,68 @@ impl Heuristic for GRCycle {
     }
 }
 
+/// Turns `solution`, a DFVS of the graph before some reductions, into a DFVS
+/// of the reduced `graph`. Vertices without arcs are dropped, and for every
+/// cycle that is left, for instance through a bypassed vertex of `solution`,
+/// the vertex with the largest degree product is added.
+pub fn restrict_solution(graph: &Graph, solution: &[u32]) -> Vec<u32> {
+    let mut restricted: Vec<_> = solution
+        .iter()
+        .copied()
+        .filter(|v| !graph.get_outgoing(v).is_empty() || !graph.get_incoming(v).is_empty())
+        .collect();
+    while let Some(cycle) = graph.find_cycle_with_fvs(&restricted) {
+        let vertex = cycle
+            .into_iter()
+            .max_by_key(|v| graph.get_incoming(v).len() * graph.get_outgoing(v).len())
+            .unwrap();
+        restricted.push(vertex);
+    }
+    restricted.sort_unstable();
+    restricted
+}
+
+/// The result of [`repair`].
+pub struct Repair {
+    pub solution: Vec<u32>,
+
+    /// The given vertices that are not vertices of the graph, which are
+    /// dropped.
+    pub invalid: Vec<u32>,
+
+    /// The vertices that are given more than once.
+    pub duplicates: Vec<u32>,
+}
+
+/// Repairs `given`, which may miss cycles of `graph` or contain vertices that
+/// are not needed. Vertices that are not in `graph` and duplicates are
+/// dropped first. The missing cycles are broken greedily and the result is
+/// shrunk to a minimal solution. The local search, started from it, may then
+/// find a smaller one.
+pub fn repair(graph: &Graph, given: &[u32]) -> Repair {
+    let (mut solution, invalid): (Vec<u32>, Vec<u32>) =
+        given.iter().partition(|v| (**v as usize) < graph.total_vertices());
+    solution.sort_unstable();
+    let mut duplicates: Vec<_> = solution.windows(2).filter(|pair| pair[0] == pair[1]).map(|pair| pair[0]).collect();
+    duplicates.dedup();
+    solution.dedup();
+    let complete = restrict_solution(graph, &solution);
+    let minimal = make_minimal(&mut graph.clone(), complete);
+
+    let searched = SimulatedAnnealing::upper_bound_from(graph, &minimal);
+    let mut repaired = if searched.len() < minimal.len() {
+        make_minimal(&mut graph.clone(), searched)
+    } else {
+        minimal
+    };
+    repaired.sort_unstable();
+    Repair {
+        solution: repaired,
+        invalid,
+        duplicates,
+    }
+}
+
 /// Reduces the solution to a minimal solution. Tries to reintroduce a vertex to
 /// the graph, and if the graph is still acyclic, we can continue. Otherwise,
 /// that vertex must be removed from the graph.
@@ -81,8 +147,41 @@ pub fn make_minimal(graph: &mut Graph, solution: Vec<u32>) -> Vec<u32> {
             graph.disable_vertex_post(vertex);
         } else {
             set.remove(&vertex);
-            println!("optimized");
         }
     }
     set.into_iter().collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::repair;
+    use crate::graph::Graph;
+
+    #[test]
+    fn repair_test_001() {
+        // a 2-cycle 0 <-> 1 and a triangle 2 -> 3 -> 4 -> 2
+        let mut graph = Graph::new(5);
+        for (u, v) in [(0, 1), (1, 0), (2, 3), (3, 4), (4, 2)] {
+            graph.add_arc(u, v);
+        }
+        // misses the triangle, and contains both vertices of the 2-cycle
+        let repaired = repair(&graph, &[0, 1, 1]);
+        assert_eq!(repaired.solution.len(), 2);
+        assert!(graph.is_acyclic_with_fvs(&repaired.solution));
+        assert_eq!(repaired.duplicates, vec![1]);
+        assert!(repaired.invalid.is_empty());
+    }
+
+    #[test]
+    fn repair_test_002() {
+        // vertex 7 is not in the graph and is dropped
+        let mut graph = Graph::new(3);
+        for (u, v) in [(0, 1), (1, 2), (2, 0)] {
+            graph.add_arc(u, v);
+        }
+        let repaired = repair(&graph, &[7, 0]);
+        assert_eq!(repaired.solution, vec![0]);
+        assert_eq!(repaired.invalid, vec![7]);
+        assert!(repaired.duplicates.is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use super::Heuristic;
+use super::{restrict_solution, Heuristic};
 use crate::{
     graph::{Compressor, Graph, HeuristicReduce},
     util::RangeSet,
@@ -59,6 +59,37 @@ impl SimulatedAnnealing {
         }
     }
 
+    /// Starts from `solution`, a DFVS of the graph before the reductions,
+    /// instead of from the empty configuration.
+    fn set_solution(&mut self, solution: &[u32]) {
+        let mut original_to_compressed = vec![None; self.mapping.iter().max().map_or(0, |v| *v as usize + 1)];
+        for (i, original) in self.mapping.iter().enumerate() {
+            original_to_compressed[*original as usize] = Some(i as u32);
+        }
+        let mapped: Vec<_> = solution
+            .iter()
+            .filter_map(|v| original_to_compressed.get(*v as usize).copied().flatten())
+            .collect();
+        let dfvs = restrict_solution(&self.graph, &mapped);
+
+        self.dfvs = RangeSet::new(self.graph.total_vertices());
+        for vertex in &dfvs {
+            self.dfvs.insert(*vertex);
+        }
+        self.conf_itov.clear();
+        for (i, vertex) in self.graph.topological_order(&dfvs).into_iter().enumerate() {
+            self.conf_itov.push(Some(vertex));
+            self.conf_vtoi[vertex as usize] = Some(i);
+        }
+    }
+
+    /// Returns an upper bound of `graph` found by starting from `solution`.
+    pub fn upper_bound_from(graph: &Graph, solution: &[u32]) -> Vec<u32> {
+        let mut sa = SimulatedAnnealing::new(graph, true);
+        sa.set_solution(solution);
+        sa.upper_bound(graph)
+    }
+
     fn out_index(&mut self, vertex: &u32) -> usize {
         // maybe we should cache this?
         // if let Some(index) = self.out_cache[*vertex as usize] {
@@ -246,8 +277,9 @@ impl SimulatedAnnealing {
 
             let mut temp = TEMPERATURE;
             let mut nb_fail = 0;
-            let mut best_len = self.graph.total_vertices();
-            let mut best_solution = Vec::with_capacity(self.graph.total_vertices());
+            // the starting configuration is the first solution
+            let mut best_solution: Vec<u32> = self.dfvs.iter().copied().collect();
+            let mut best_len = best_solution.len();
 
             let ud = Uniform::new(0., 1.);
 
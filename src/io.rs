@@ -1,4 +1,4 @@
-use crate::graph::Graph;
+use crate::{graph::Graph, util::Constraint};
 use clap::Parser;
 use io::Result;
 use std::{
@@ -102,6 +102,66 @@ pub fn write(solution: Vec<u32>) {
     }
 }
 
+/// Reads a hitting set instance in the format of PACE 2025 from stdin: the
+/// line `p hs <elements> <sets>` is followed by a line of 1-indexed elements
+/// for every set, and lines starting with `c` are comments. Returns the
+/// number of elements and the sets, or an error of kind `InvalidData` if the
+/// instance does not match its header or contains an empty set.
+pub fn read_hitting_set() -> Result<(usize, Vec<Constraint>)> {
+    let invalid = |number: usize, message: String| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", number + 1, message))
+    };
+    let stdin = io::stdin();
+    let mut header = None;
+    let mut sets = Vec::new();
+    for (number, line) in stdin.lock().lines().enumerate() {
+        let line = line?;
+        if line.starts_with('c') {
+            continue;
+        }
+        if let Some(specs) = line.strip_prefix("p hs") {
+            let specs: Vec<_> = specs.split_whitespace().map(|v| v.parse::<usize>()).collect();
+            match specs[..] {
+                [Ok(elements), Ok(count)] if header.is_none() => {
+                    header = Some((elements, count));
+                    sets.reserve(count);
+                }
+                _ => return Err(invalid(number, format!("invalid header {}", line))),
+            }
+            continue;
+        }
+
+        let (elements, _) = header.ok_or_else(|| invalid(number, "set before the header".to_string()))?;
+        let set = line
+            .split_whitespace()
+            .map(|v| match v.parse::<u32>() {
+                Ok(element) if element >= 1 && element as usize <= elements => Ok(element - 1),
+                _ => Err(invalid(number, format!("{} is not an element", v))),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if set.is_empty() {
+            return Err(invalid(number, "empty set".to_string()));
+        }
+        sets.push(Constraint::new(set, 1));
+    }
+
+    let (elements, count) = header.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing header"))?;
+    if sets.len() != count {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("expected {} sets, found {}", count, sets.len()),
+        ));
+    }
+    Ok((elements, sets))
+}
+
+/// Writes a hitting set in the format of PACE 2025, its size followed by
+/// its 1-indexed elements.
+pub fn write_hitting_set(solution: Vec<u32>) {
+    println!("{}", solution.len());
+    write(solution);
+}
+
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
 pub struct Config {
@@ -116,14 +176,124 @@ pub struct Config {
     algorithm: Algorithm,
 
     #[clap(short, long, default_value_t = 1)]
-    frequency: usize
+    frequency: usize,
+
+    /// Cycles added to the implicit hitting set instance whenever its
+    /// solution is not a DFVS
+    #[clap(value_enum, long, default_value_t = Cores::Disjoint)]
+    cores: Cores,
+
+    /// Branching rule of the branch-and-reduce algorithm
+    #[clap(value_enum, short, long, default_value_t = Branching::StarCycle)]
+    branching: Branching,
+
+    /// Number of threads of the branch-and-reduce algorithm
+    #[clap(short = 'j', long, default_value_t = 1)]
+    threads: usize,
+
+    /// Search strategy of the branch-and-reduce algorithm
+    #[clap(value_enum, short, long, default_value_t = Strategy::Dfs)]
+    strategy: Strategy,
+
+    /// Memory limit of the open nodes of the best-first search (MB), after
+    /// which it continues depth-first
+    #[clap(short, long, default_value_t = 4096)]
+    memory_limit: usize,
+
+    /// Memory limit of the transposition table of the branch-and-reduce
+    /// algorithm (MB), 0 disables the table
+    #[clap(long, default_value_t = 256)]
+    table_limit: usize,
+
+    /// File to which the state of the exact algorithm is written periodically
+    #[clap(long)]
+    checkpoint: Option<PathBuf>,
+
+    /// Time between two checkpoints (s)
+    #[clap(long, default_value_t = 600)]
+    checkpoint_interval: u64,
+
+    /// Continue from the checkpoint file instead of reading a graph
+    #[clap(long, requires = "checkpoint")]
+    resume: bool,
+
+    /// Break the symmetries of the graph in the ILP and branch on orbits in
+    /// the branch-and-reduce algorithm
+    #[clap(long)]
+    symmetry: bool,
+
+    /// Write the ILP of the graph to this file instead of solving it, as MPS
+    /// if the extension is mps, as weighted MaxSAT if it is wcnf and as
+    /// CPLEX-LP otherwise
+    #[clap(long)]
+    export: Option<PathBuf>,
+
+    /// Export the LP relaxation instead of the ILP
+    #[clap(long, requires = "export")]
+    relaxed: bool,
+
+    /// Encode acyclicity completely in the WCNF export with the transitive
+    /// closure, instead of only the cycles found so far
+    #[clap(long, requires = "export")]
+    closure: bool,
+
+    /// Solution file of the exported model, written by another solver, to
+    /// verify against the graph
+    #[clap(long, requires = "export")]
+    verify: Option<PathBuf>,
+
+    /// Solution file of the graph, e.g. of a previous run, which is used as
+    /// the first incumbent
+    #[clap(long)]
+    initial_solution: Option<PathBuf>,
+
+    /// Solution file to repair into a minimal DFVS of the graph, instead of
+    /// solving the graph
+    #[clap(long)]
+    repair: Option<PathBuf>,
+
+    /// Vertex whose cycle dominators, the vertices on all cycles through it,
+    /// are listed instead of solving the graph
+    #[clap(long)]
+    cycle_dominators: Option<u32>,
 }
 
 #[derive(clap::ValueEnum, Clone, Debug)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum Algorithm {
     ILP,
     BNR,
     BNB,
+    /// ILP with cycle constraints separated in a callback
+    BNC,
+    /// ILP with vertex potentials that order the kept vertices
+    MTZ,
+    /// Incremental SAT with lazy cycle clauses, needs no MIP solver
+    SAT,
+    /// Implicit hitting set with an exact branch and bound as oracle, needs
+    /// no MIP solver
+    IHS,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum Branching {
+    StarCycle,
+    MaxDegree,
+    MostCycles,
+    LpFractional,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum Cores {
+    Disjoint,
+    Shortest,
+    Minimal,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum Strategy {
+    Dfs,
+    BestFirst,
 }
 
 impl Config {
@@ -142,22 +312,118 @@ impl Config {
     pub fn frequency(&self) -> usize {
         self.frequency
     }
+
+    pub fn cores(&self) -> &Cores {
+        &self.cores
+    }
+
+    pub fn branching(&self) -> &Branching {
+        &self.branching
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    pub fn strategy(&self) -> &Strategy {
+        &self.strategy
+    }
+
+    pub fn memory_limit(&self) -> usize {
+        self.memory_limit
+    }
+
+    pub fn table_limit(&self) -> usize {
+        self.table_limit
+    }
+
+    pub fn checkpoint(&self) -> &Option<PathBuf> {
+        &self.checkpoint
+    }
+
+    pub fn checkpoint_interval(&self) -> u64 {
+        self.checkpoint_interval
+    }
+
+    pub fn resume(&self) -> bool {
+        self.resume
+    }
+
+    pub fn symmetry(&self) -> bool {
+        self.symmetry
+    }
+
+    pub fn export(&self) -> &Option<PathBuf> {
+        &self.export
+    }
+
+    pub fn relaxed(&self) -> bool {
+        self.relaxed
+    }
+
+    pub fn closure(&self) -> bool {
+        self.closure
+    }
+
+    pub fn verify(&self) -> &Option<PathBuf> {
+        &self.verify
+    }
+
+    pub fn initial_solution(&self) -> &Option<PathBuf> {
+        &self.initial_solution
+    }
+
+    pub fn repair(&self) -> &Option<PathBuf> {
+        &self.repair
+    }
+
+    pub fn cycle_dominators(&self) -> Option<u32> {
+        self.cycle_dominators
+    }
 }
 
 pub fn config() -> Config {
     Config::parse()
 }
  
-pub fn load_solution(path: &PathBuf) -> Vec<u32> {
-    let file = File::open(path).unwrap();
+/// Reads a list of vertices with one vertex per line, as written by
+/// [`write`]. Empty lines are skipped, and other lines that are not vertices
+/// are an error.
+pub fn load_vertices(path: &PathBuf) -> Result<Vec<u32>> {
+    let file = File::open(path)?;
     let reader = BufReader::new(file);
 
-    let mut solution = Vec::new();
+    let mut vertices = Vec::new();
 
-    for line in reader.lines() {
-        let vertex = line.unwrap().parse::<u32>().unwrap() - 1;
-        solution.push(vertex);
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match line.parse::<u32>() {
+            Ok(vertex) if vertex >= 1 => vertices.push(vertex - 1),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: {} is not a vertex", number + 1, line),
+                ))
+            }
+        }
     }
 
-    solution
+    Ok(vertices)
+}
+
+/// Reads a solution with [`load_vertices`], which fails if it contains a
+/// vertex that is not in a graph with `vertices` vertices.
+pub fn load_solution(path: &PathBuf, vertices: usize) -> Result<Vec<u32>> {
+    let solution = load_vertices(path)?;
+    if let Some(vertex) = solution.iter().find(|v| **v as usize >= vertices) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} is not a vertex of the graph", vertex + 1),
+        ));
+    }
+    Ok(solution)
 }
\ No newline at end of file
@@ -1,12 +1,13 @@
+use super::{Stall, CUT_TOLERANCE, MAX_ROUNDS};
 use crate::{
     exact,
-    graph::{EdgeCycleCover, EdgeIter, Graph, ThreeCliques, FourCliques, TwinCliques},
+    graph::{CliqueCover, EdgeCycleCover, EdgeIter, Graph, ThreeCliques, FourCliques, TwinCliques, WeightedCycles},
     heur::hitting_set_upper_bound_custom,
     util::Constraint,
 };
 use grb::{expr::LinExpr, prelude::*};
 
-pub fn lower_bound(graph: &Graph, undirected_graph: &Graph) -> (f64, Vec<u32>) {
+pub fn lower_bound(graph: &Graph, undirected_graph: &Graph) -> (f64, Vec<u32>, Vec<f64>) {
     let _out = shh::stdout();
     let vertices = graph.total_vertices();
 
@@ -83,6 +84,48 @@ pub fn lower_bound(graph: &Graph, undirected_graph: &Graph) -> (f64, Vec<u32>) {
         model.add_constr("", c!(expr >= lb)).unwrap();
     }
 
+    // triangles and 4-cliques are already added, only larger cliques remain
+    for clique in undirected_graph.clique_cover() {
+        if clique.len() <= 4 {
+            continue;
+        }
+        let lb = clique.len() - 1;
+        let mut expr = LinExpr::new();
+        for v in clique {
+            expr.add_term(1., vars[v as usize]);
+        }
+        model.add_constr("", c!(expr >= lb)).unwrap();
+    }
+
+    // cutting planes: add the cycles violated by the fractional optimum
+    // until there are none or the bound no longer improves
+    let combined = graph.clone() + undirected_graph.clone();
     model.optimize().unwrap();
-    (model.get_attr(attr::ObjVal).unwrap(), upper_bound)
+    let mut stall = Stall::new(model.get_attr(attr::ObjVal).unwrap());
+    for _ in 0..MAX_ROUNDS {
+        let cycles = combined.light_cycles(&values(&model, &vars), 1. - CUT_TOLERANCE);
+        if cycles.is_empty() {
+            break;
+        }
+        for cycle in cycles {
+            let mut expr = LinExpr::new();
+            for v in cycle {
+                expr.add_term(1., vars[v as usize]);
+            }
+            model.add_constr("", c!(expr >= 1)).unwrap();
+        }
+        model.optimize().unwrap();
+        if stall.update(model.get_attr(attr::ObjVal).unwrap()) {
+            break;
+        }
+    }
+
+    let values = values(&model, &vars);
+    (model.get_attr(attr::ObjVal).unwrap(), upper_bound, values)
+}
+
+fn values(model: &Model, vars: &[Var]) -> Vec<f64> {
+    vars.iter()
+        .map(|var| model.get_obj_attr(attr::X, var).unwrap())
+        .collect()
 }
@@ -1,16 +1,56 @@
 //! Module to compute lower bounds for the graph
 use crate::graph::Graph;
 mod grb_rilp;
+mod relaxation;
 // mod cycle_rilp;
 // mod ecc_rilp;
 // mod vc_rilp;
 // mod vcsr_rilp;
+pub use relaxation::Relaxation;
 
-pub fn lower_bound(gd: &Graph, gb: &Graph) -> (usize, Vec<u32>) {
-    let (rlb, candidate) = raw_lower_bound(gd, gb);
-    ((rlb - 1e-5).ceil() as usize, candidate)
+/// Returns the rounded up LP lower bound, a heuristic candidate solution and
+/// the value of every vertex in the optimal LP solution.
+pub fn lower_bound(gd: &Graph, gb: &Graph) -> (usize, Vec<u32>, Vec<f64>) {
+    let (rlb, candidate, values) = raw_lower_bound(gd, gb);
+    ((rlb - 1e-5).ceil() as usize, candidate, values)
 }
 
-pub fn raw_lower_bound(gd: &Graph, gb: &Graph) -> (f64, Vec<u32>) {
+pub fn raw_lower_bound(gd: &Graph, gb: &Graph) -> (f64, Vec<u32>, Vec<f64>) {
     grb_rilp::lower_bound(gd, gb)
 }
+
+/// Fractional cycles are separated if they are lighter than 1 by more than
+/// this.
+const CUT_TOLERANCE: f64 = 1e-3;
+
+/// Upper limit on the rounds of fractional cycle separation.
+const MAX_ROUNDS: usize = 50;
+
+/// Tracks the objective of a cutting-plane loop, which has stalled once it
+/// improved by less than `STALL_IMPROVEMENT` in `STALL_ROUNDS` consecutive
+/// rounds.
+struct Stall {
+    bound: f64,
+    rounds: usize,
+}
+
+const STALL_IMPROVEMENT: f64 = 1e-3;
+const STALL_ROUNDS: usize = 3;
+
+impl Stall {
+    fn new(bound: f64) -> Stall {
+        Stall { bound, rounds: 0 }
+    }
+
+    /// Records the objective of the next round, and returns whether the loop
+    /// has stalled.
+    fn update(&mut self, bound: f64) -> bool {
+        if bound - self.bound < STALL_IMPROVEMENT {
+            self.rounds += 1;
+        } else {
+            self.rounds = 0;
+        }
+        self.bound = bound;
+        self.rounds >= STALL_ROUNDS
+    }
+}
@@ -0,0 +1,298 @@
+//! LP relaxation that persists across the nodes of the branch-and-reduce
+//! algorithm. All graphs of the search share the vertex set of the root, so
+//! the variables are created once. Every node only changes the bounds of the
+//! variables and the right-hand sides of the rows in the pool, such that the
+//! dual simplex can start from the basis of the previous node.
+use super::{Stall, CUT_TOLERANCE, MAX_ROUNDS};
+use crate::{
+    exact,
+    graph::{CliqueCover, EdgeCycleCover, EdgeIter, FourCliques, Graph, ThreeCliques, TwinCliques, WeightedCycles},
+};
+use grb::{expr::LinExpr, prelude::*};
+use rustc_hash::FxHashMap;
+
+struct Row {
+    /// The sorted vertices of the row.
+    vertices: Vec<u32>,
+
+    /// Whether the row is a clique row, *sum >= |C| - 1*, instead of a cycle
+    /// row, *sum >= 1*.
+    clique: bool,
+
+    constr: Constr,
+
+    /// Whether the row is valid in the graph of the current node.
+    active: bool,
+}
+
+impl Row {
+    fn rhs(&self) -> f64 {
+        if self.clique {
+            (self.vertices.len() - 1) as f64
+        } else {
+            1.
+        }
+    }
+}
+
+pub struct Relaxation {
+    model: Model,
+    vars: Vec<Var>,
+
+    /// The current upper bound of every variable.
+    upper: Vec<f64>,
+
+    rows: Vec<Row>,
+    index: FxHashMap<(bool, Vec<u32>), usize>,
+}
+
+impl Relaxation {
+    pub fn new(vertices: usize) -> Relaxation {
+        let mut model = exact::init_model();
+        model.set_param(param::Method, 1).unwrap();
+
+        let mut vars = Vec::with_capacity(vertices);
+        for i in 0..vertices {
+            let n = format!("v{}", i);
+            let var = add_ctsvar!(model, name: &n, bounds: 0..1).unwrap();
+            vars.push(var);
+        }
+        model
+            .set_objective(vars.iter().sum::<Expr>(), Minimize)
+            .unwrap();
+        model.update().unwrap();
+
+        Relaxation {
+            model,
+            vars,
+            upper: vec![1.; vertices],
+            rows: Vec::new(),
+            index: FxHashMap::default(),
+        }
+    }
+
+    pub fn vertices(&self) -> usize {
+        self.vars.len()
+    }
+
+    /// Removes all rows and the basis, such that the next bound does not
+    /// depend on the nodes bounded before.
+    pub fn clear(&mut self) {
+        for row in self.rows.drain(..) {
+            self.model.remove(row.constr).unwrap();
+        }
+        self.index.clear();
+        self.model.reset().unwrap();
+    }
+
+    /// Returns the rounded up LP lower bound, a candidate solution and the
+    /// value of every vertex in the optimal LP solution of the node given by
+    /// the directed graph `gd` and undirected graph `gb` of the split.
+    pub fn lower_bound(&mut self, gd: &Graph, gb: &Graph) -> (usize, Vec<u32>, Vec<f64>) {
+        let (rlb, candidate, values) = self.raw_lower_bound(gd, gb);
+        ((rlb - 1e-5).ceil() as usize, candidate, values)
+    }
+
+    pub fn raw_lower_bound(&mut self, gd: &Graph, gb: &Graph) -> (f64, Vec<u32>, Vec<f64>) {
+        let _out = shh::stdout();
+        let active = gd.get_active_vertices();
+        let mut is_active = vec![false; self.vars.len()];
+        for v in &active {
+            is_active[*v as usize] = true;
+        }
+
+        // deleted vertices are either in the solution or contracted, in both
+        // cases they no longer contribute to the objective
+        for (v, is_active) in is_active.iter().enumerate() {
+            let upper = if *is_active { 1. } else { 0. };
+            if self.upper[v] != upper {
+                self.model
+                    .set_obj_attr(attr::UB, &self.vars[v], upper)
+                    .unwrap();
+                self.upper[v] = upper;
+            }
+        }
+
+        for i in 0..self.rows.len() {
+            let row = &self.rows[i];
+            let valid = if row.clique {
+                is_clique(gb, &row.vertices, &is_active)
+            } else {
+                is_cyclic_within(gd, gb, &row.vertices, &is_active)
+            };
+            if valid != row.active {
+                let rhs = if valid { row.rhs() } else { 0. };
+                self.model.set_obj_attr(attr::RHS, &row.constr, rhs).unwrap();
+                self.rows[i].active = valid;
+            }
+        }
+
+        for (u, v) in gb.undir_edge_iter() {
+            self.add_row(vec![u, v], false);
+        }
+        for (a, b, c) in gb.undirected_three_cliques() {
+            self.add_row(vec![a, b, c], true);
+        }
+        for clique in gb.four_cliques() {
+            self.add_row(clique.to_vec(), true);
+        }
+        for twin in gb.twin_cliques() {
+            self.add_row(twin, true);
+        }
+        for clique in gb.clique_cover() {
+            self.add_row(clique, true);
+        }
+
+        // Cycles missed by the rounded optimum and cycles violated by the
+        // fractional optimum are added until there are none, or until the
+        // bound no longer improves.
+        let combined = gd.clone() + gb.clone();
+        let mut iter = 5;
+        self.model.optimize().unwrap();
+        let mut stall = Stall::new(self.model.get_attr(attr::ObjVal).unwrap());
+        for _ in 0..MAX_ROUNDS {
+            let values = self.values();
+            let candidate = rounded(&active, &values);
+
+            let mut added = false;
+            if iter > 0 && !gd.is_acyclic_with_fvs(&candidate) {
+                for cycle in gd.disjoint_edge_cycle_cover(&candidate) {
+                    added |= self.add_row(cycle, false);
+                }
+                iter -= 1;
+            }
+            for cycle in combined.light_cycles(&values, 1. - CUT_TOLERANCE) {
+                added |= self.add_row(cycle, false);
+            }
+            if !added {
+                break;
+            }
+
+            self.model.optimize().unwrap();
+            if stall.update(self.model.get_attr(attr::ObjVal).unwrap()) {
+                break;
+            }
+        }
+
+        let values = self.values();
+        let candidate = rounded(&active, &values);
+        (self.model.get_attr(attr::ObjVal).unwrap(), candidate, values)
+    }
+
+    fn values(&self) -> Vec<f64> {
+        self.vars
+            .iter()
+            .map(|var| self.model.get_obj_attr(attr::X, var).unwrap())
+            .collect()
+    }
+
+    /// Adds the row over `vertices` to the pool, unless it is already there.
+    /// Returns whether a row was added.
+    fn add_row(&mut self, mut vertices: Vec<u32>, clique: bool) -> bool {
+        vertices.sort_unstable();
+        let key = (clique, vertices);
+        if let Some(&i) = self.index.get(&key) {
+            let row = &mut self.rows[i];
+            if !row.active {
+                let rhs = row.rhs();
+                self.model.set_obj_attr(attr::RHS, &row.constr, rhs).unwrap();
+                row.active = true;
+            }
+            return false;
+        }
+
+        let (clique, vertices) = key;
+        let mut expr = LinExpr::new();
+        for v in &vertices {
+            expr.add_term(1., self.vars[*v as usize]);
+        }
+        let rhs = if clique { vertices.len() - 1 } else { 1 };
+        let constr = self.model.add_constr("", c!(expr >= rhs)).unwrap();
+
+        self.index.insert((clique, vertices.clone()), self.rows.len());
+        self.rows.push(Row {
+            vertices,
+            clique,
+            constr,
+            active: true,
+        });
+        true
+    }
+}
+
+/// Returns the `active` vertices with a value of at least 1/2.
+fn rounded(active: &[u32], values: &[f64]) -> Vec<u32> {
+    active
+        .iter()
+        .copied()
+        .filter(|v| values[*v as usize] >= 0.5)
+        .collect()
+}
+
+/// Returns whether all `vertices` are active and pairwise adjacent in `gb`.
+fn is_clique(gb: &Graph, vertices: &[u32], is_active: &[bool]) -> bool {
+    vertices.iter().enumerate().all(|(i, u)| {
+        is_active[*u as usize]
+            && vertices[i + 1..]
+                .iter()
+                .all(|v| gb.get_outgoing(u).binary_search(v).is_ok())
+    })
+}
+
+/// Returns whether all `vertices` are active and the subgraph of `gd` and `gb`
+/// induced by `vertices` contains a cycle, i.e., whether the cycle row over
+/// `vertices` is still valid.
+fn is_cyclic_within(gd: &Graph, gb: &Graph, vertices: &[u32], is_active: &[bool]) -> bool {
+    if vertices.iter().any(|v| !is_active[*v as usize]) {
+        return false;
+    }
+
+    // repeatedly remove the vertices without incoming arcs
+    let inside = |v: &u32| vertices.binary_search(v).is_ok();
+    let mut degree: Vec<usize> = vertices
+        .iter()
+        .map(|v| {
+            let directed = gd.get_incoming(v).iter().filter(|u| inside(u)).count();
+            let undirected = gb.get_incoming(v).iter().filter(|u| inside(u)).count();
+            directed + undirected
+        })
+        .collect();
+    let mut queue: Vec<usize> = (0..vertices.len()).filter(|i| degree[*i] == 0).collect();
+    let mut removed = 0;
+    while let Some(i) = queue.pop() {
+        removed += 1;
+        let v = &vertices[i];
+        for u in gd.get_outgoing(v).iter().chain(gb.get_outgoing(v)) {
+            if let Ok(j) = vertices.binary_search(u) {
+                degree[j] -= 1;
+                if degree[j] == 0 {
+                    queue.push(j);
+                }
+            }
+        }
+    }
+    removed < vertices.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_cyclic_within;
+    use crate::graph::Graph;
+
+    #[test]
+    fn is_cyclic_within_test_001() {
+        // a triangle 0 -> 1 -> 2 -> 0 and a path 2 -> 3 -> 4
+        let mut gd = Graph::new(5);
+        gd.add_arc(0, 1);
+        gd.add_arc(1, 2);
+        gd.add_arc(2, 0);
+        gd.add_arc(2, 3);
+        gd.add_arc(3, 4);
+        let gb = Graph::new(5);
+        let active = vec![true; 5];
+        assert!(is_cyclic_within(&gd, &gb, &[0, 1, 2], &active));
+        assert!(is_cyclic_within(&gd, &gb, &[0, 1, 2, 3], &active));
+        assert!(!is_cyclic_within(&gd, &gb, &[1, 2, 3, 4], &active));
+        assert!(!is_cyclic_within(&gd, &gb, &[0, 1, 2], &[false, true, true, true, true]));
+    }
+}
@@ -10,9 +10,82 @@ mod io;
 mod lower;
 mod util;
 
+use graph::{Dominators, Graph};
+
 fn main() {
     let config = io::config();
-    let graph = io::read().unwrap();
-    let solution = exact::solve(graph, &config);
+    let solution = if config.resume() {
+        match exact::resume(&config) {
+            Ok(solution) => solution,
+            Err(err) => {
+                eprintln!("cannot resume: {}", err);
+                std::process::exit(1);
+            }
+        }
+    } else if config.verify().is_some() {
+        let graph = io::read().unwrap();
+        match exact::export::verify(&graph, &config) {
+            Some(solution) => {
+                eprintln!("valid DFVS of size {}", solution.len());
+                solution
+            }
+            None => {
+                eprintln!("the solution is not a DFVS");
+                std::process::exit(1);
+            }
+        }
+    } else if let Some(path) = config.repair() {
+        let graph = io::read().unwrap();
+        let given = match io::load_vertices(path) {
+            Ok(given) => given,
+            Err(err) => {
+                eprintln!("cannot read the solution: {}", err);
+                std::process::exit(1);
+            }
+        };
+        let repair = heur::repair(&graph, &given);
+        report_repair(&given, &repair);
+        repair.solution
+    } else if let Some(vertex) = config.cycle_dominators() {
+        let graph = io::read().unwrap();
+        report_cycle_dominators(&graph, vertex);
+        return;
+    } else if config.export().is_some() {
+        let graph = io::read().unwrap();
+        exact::export::export(graph, &config);
+        return;
+    } else {
+        let graph = io::read().unwrap();
+        exact::solve(graph, &config)
+    };
     io::write(solution);
 }
+
+/// Reports the vertices of `given` that were dropped, and the vertices that
+/// the repair added to and removed from the rest.
+fn report_repair(given: &[u32], repair: &heur::Repair) {
+    let repaired = &repair.solution;
+    let kept: Vec<_> = given.iter().filter(|v| !repair.invalid.contains(v)).copied().collect();
+    eprintln!("given = {}, repaired = {}", given.len(), repaired.len());
+    eprintln!("invalid: {}", vertex_line(repair.invalid.iter()));
+    eprintln!("duplicates: {}", vertex_line(repair.duplicates.iter()));
+    eprintln!("added: {}", vertex_line(repaired.iter().filter(|v| !kept.contains(v))));
+    eprintln!("removed: {}", vertex_line(kept.iter().filter(|v| !repaired.contains(v))));
+}
+
+/// Lists the vertices on all cycles through `vertex`, ordered from `vertex`
+/// outwards, or nothing if `vertex` is on no cycle. Vertices are 1-indexed.
+fn report_cycle_dominators(graph: &Graph, vertex: u32) {
+    if vertex == 0 || vertex as usize > graph.total_vertices() {
+        eprintln!("{} is not a vertex of the graph", vertex);
+        std::process::exit(1);
+    }
+    let dominators = graph.cycle_dominators(vertex - 1);
+    println!("{}", vertex_line(dominators.iter()));
+}
+
+/// Returns the vertices 1-indexed on a single line.
+fn vertex_line<'a>(vertices: impl Iterator<Item = &'a u32>) -> String {
+    let vertices: Vec<_> = vertices.map(|v| (v + 1).to_string()).collect();
+    vertices.join(" ")
+}
@@ -107,10 +107,12 @@ pub struct Heap<T: Ord + KeyValue> {
 }
 
 impl<T: Ord + KeyValue> Heap<T> {
-    pub fn new(_range: usize) -> Heap<T> {
+    /// Creates an empty heap for the keys `0..range`, the range grows as keys
+    /// outside of it are inserted.
+    pub fn new(range: usize) -> Heap<T> {
         Heap {
             heap: Vec::new(),
-            table: Vec::new(),
+            table: vec![0; range],
         }
     }
 
@@ -168,6 +170,9 @@ impl<T: Ord + KeyValue> Heap<T> {
     }
 
     pub fn extract_min(&mut self) -> Option<T> {
+        if self.len() == 0 {
+            return None;
+        }
         let last = self.len() - 1;
 
         let i = self.heap[0].key();
@@ -183,9 +188,11 @@ impl<T: Ord + KeyValue> Heap<T> {
         min
     }
 
-    #[allow(dead_code)]
     pub fn insert(&mut self, element: T) {
         let i = element.key();
+        if i >= self.table.len() {
+            self.table.resize(i + 1, 0);
+        }
         self.heap.push(element);
         let mut index = self.len() - 1;
         self.table[i] = index;
@@ -285,4 +292,16 @@ mod tests {
         assert_eq!(heap.get_index(0), 0);
         assert_eq!(heap.get_index(1), 1);
     }
+
+    #[test]
+    fn extracting() {
+        let mut heap = Heap::new(0);
+        heap.insert(MinItem::new(7, 3));
+        heap.insert(MinItem::new(2, 1));
+        heap.insert(MinItem::new(4, 2));
+        assert_eq!(heap.extract_min().unwrap().key(), 2);
+        assert_eq!(heap.extract_min().unwrap().key(), 4);
+        assert_eq!(heap.extract_min().unwrap().key(), 7);
+        assert!(heap.extract_min().is_none());
+    }
 }
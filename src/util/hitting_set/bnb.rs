@@ -0,0 +1,161 @@
+use crate::util::Constraint;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    Free,
+    Taken,
+    Excluded,
+}
+
+struct Search<'a> {
+    constraints: &'a [Constraint],
+    states: Vec<State>,
+    current: Vec<u32>,
+    best: Vec<u32>,
+}
+
+impl Search<'_> {
+    /// Returns the number of variables a constraint still needs, and the
+    /// number of free variables it can take them from.
+    fn demand(&self, constraint: &Constraint) -> (usize, usize) {
+        let mut taken = 0;
+        let mut free = 0;
+        for variable in constraint.variables() {
+            match self.states[*variable as usize] {
+                State::Taken => taken += 1,
+                State::Free => free += 1,
+                State::Excluded => {}
+            }
+        }
+        ((constraint.lower_bound() as usize).saturating_sub(taken), free)
+    }
+
+    fn branch(&mut self) {
+        if self.current.len() >= self.best.len() {
+            return;
+        }
+
+        // the open constraint with the fewest choices
+        let mut open = Vec::new();
+        let mut chosen: Option<(usize, usize)> = None;
+        for (i, constraint) in self.constraints.iter().enumerate() {
+            let (demand, free) = self.demand(constraint);
+            if demand == 0 {
+                continue;
+            }
+            if demand > free {
+                return;
+            }
+            open.push(i);
+            if chosen.is_none_or(|(_, choices)| free - demand < choices) {
+                chosen = Some((i, free - demand));
+            }
+        }
+        let chosen = match chosen {
+            Some((chosen, _)) => chosen,
+            None => {
+                self.best = self.current.clone();
+                return;
+            }
+        };
+
+        let lower_bound = self.packing_bound(&open);
+        if self.current.len() + lower_bound >= self.best.len() {
+            return;
+        }
+
+        // the first free variable that is taken is tried in turn, and the
+        // variables before it are excluded
+        let constraints = self.constraints;
+        let mut excluded = Vec::new();
+        for variable in constraints[chosen].variables() {
+            if self.states[*variable as usize] != State::Free {
+                continue;
+            }
+            self.states[*variable as usize] = State::Taken;
+            self.current.push(*variable);
+            self.branch();
+            self.current.pop();
+            self.states[*variable as usize] = State::Excluded;
+            excluded.push(*variable);
+        }
+        for variable in excluded {
+            self.states[variable as usize] = State::Free;
+        }
+    }
+
+    /// Returns the demand of a greedy packing of open constraints whose free
+    /// variables are disjoint.
+    fn packing_bound(&self, open: &[usize]) -> usize {
+        let mut order: Vec<_> = open
+            .iter()
+            .map(|i| {
+                let (demand, free) = self.demand(&self.constraints[*i]);
+                (free, demand, *i)
+            })
+            .collect();
+        order.sort_unstable();
+
+        let mut used = vec![false; self.states.len()];
+        let mut bound = 0;
+        for (_, demand, i) in order {
+            let variables = self.constraints[i].variables();
+            let free = |v: &&u32| self.states[**v as usize] == State::Free;
+            if variables.iter().filter(free).any(|v| used[*v as usize]) {
+                continue;
+            }
+            for v in variables.iter().filter(free) {
+                used[*v as usize] = true;
+            }
+            bound += demand;
+        }
+        bound
+    }
+}
+
+/// Returns a minimum set of variables below `variables` that contains at
+/// least the lower bound of the variables of every constraint, by branch and
+/// bound. `upper_bound` has to be a feasible solution.
+pub fn minimum_hitting_set(constraints: &[Constraint], variables: usize, upper_bound: Vec<u32>) -> Vec<u32> {
+    let mut search = Search {
+        constraints,
+        states: vec![State::Free; variables],
+        current: Vec::new(),
+        best: upper_bound,
+    };
+    search.branch();
+    let mut best = search.best;
+    best.sort_unstable();
+    best
+}
+
+/// Returns a lower bound on the size of any hitting set of `constraints`,
+/// from a greedy packing of constraints with disjoint variables.
+pub fn packing_lower_bound(constraints: &[Constraint], variables: usize) -> usize {
+    let search = Search {
+        constraints,
+        states: vec![State::Free; variables],
+        current: Vec::new(),
+        best: Vec::new(),
+    };
+    let open: Vec<_> = (0..constraints.len()).collect();
+    search.packing_bound(&open)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{minimum_hitting_set, packing_lower_bound};
+    use crate::util::Constraint;
+
+    #[test]
+    fn minimum_hitting_set_test_001() {
+        // the cycle 0 - 1 - 2 - 3 - 4 - 0 and a triangle with demand 2
+        let mut constraints: Vec<_> = (0..5).map(|i| Constraint::new(vec![i, (i + 1) % 5], 1)).collect();
+        constraints.push(Constraint::new(vec![5, 6, 7], 2));
+        assert_eq!(packing_lower_bound(&constraints, 8), 4);
+
+        let solution = minimum_hitting_set(&constraints, 8, (0..8).collect());
+        assert_eq!(solution.len(), 5);
+        assert_eq!(solution.iter().filter(|v| **v >= 5).count(), 2);
+    }
+}
@@ -4,6 +4,9 @@ use rustc_hash::FxHashSet;
 use super::algorithms::difference;
 use super::algorithms::intersection;
 
+mod bnb;
+pub use bnb::{minimum_hitting_set, packing_lower_bound};
+
 pub struct HSReductionResult {
     pub forced: Vec<u32>,
     pub reduced: Vec<Vec<u32>>,
@@ -2,8 +2,10 @@ mod constraint;
 mod heap;
 mod hitting_set;
 mod rangeset;
+mod sat;
 pub use constraint::Constraint;
 pub use heap::{Heap, KeyValue, MaxItem, MinItem};
-pub use hitting_set::{reduce_hitting_set, HSReductionResult};
+pub use hitting_set::{minimum_hitting_set, packing_lower_bound, reduce_hitting_set};
 pub use rangeset::RangeSet;
+pub use sat::{Lit, Solver, Totalizer};
 pub mod algorithms;
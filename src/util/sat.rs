@@ -0,0 +1,477 @@
+//! A small CDCL SAT solver: two watched literals, first-UIP clause learning,
+//! VSIDS with phase saving and Luby restarts. Clauses can be added between
+//! calls to [`Solver::solve`], and the learnt clauses stay valid, so the solver
+//! can be used incrementally. Learnt clauses are never deleted.
+use std::{collections::BinaryHeap, ops::Not};
+
+/// Number of conflicts of the shortest run between restarts.
+const RESTART_UNIT: u64 = 100;
+
+/// Factor by which the activity of the variables decays after a conflict.
+const DECAY: f64 = 0.95;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Lit(u32);
+
+impl Lit {
+    pub fn positive(var: u32) -> Lit {
+        Lit(var << 1)
+    }
+
+    pub fn negative(var: u32) -> Lit {
+        Lit(var << 1 | 1)
+    }
+
+    pub fn var(self) -> u32 {
+        self.0 >> 1
+    }
+
+    fn is_negative(self) -> bool {
+        self.0 & 1 == 1
+    }
+
+    fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl Not for Lit {
+    type Output = Lit;
+
+    fn not(self) -> Lit {
+        Lit(self.0 ^ 1)
+    }
+}
+
+fn value(values: &[Option<bool>], lit: Lit) -> Option<bool> {
+    values[lit.var() as usize].map(|value| value != lit.is_negative())
+}
+
+#[derive(Default)]
+pub struct Solver {
+    clauses: Vec<Vec<Lit>>,
+
+    /// The clauses to visit once a literal is true, which are the clauses
+    /// watching its negation.
+    watches: Vec<Vec<usize>>,
+
+    values: Vec<Option<bool>>,
+    levels: Vec<usize>,
+    reasons: Vec<Option<usize>>,
+    trail: Vec<Lit>,
+
+    /// The position in the trail at which every decision level starts.
+    limits: Vec<usize>,
+
+    /// The position in the trail up to which the literals are propagated.
+    head: usize,
+
+    activity: Vec<f64>,
+    increment: f64,
+
+    /// The unassigned variables by their activity. Entries become stale when
+    /// the activity changes, so they are compared to the current activity
+    /// when they are popped. Activities are not negative, so the order of
+    /// their bits is the order of the activities.
+    order: BinaryHeap<(u64, u32)>,
+
+    phases: Vec<bool>,
+    seen: Vec<bool>,
+
+    /// Whether the clauses are unsatisfiable regardless of later clauses.
+    inconsistent: bool,
+    model: Vec<bool>,
+}
+
+impl Solver {
+    pub fn new() -> Solver {
+        Solver {
+            increment: 1.0,
+            ..Default::default()
+        }
+    }
+
+    pub fn new_var(&mut self) -> u32 {
+        let var = self.values.len() as u32;
+        self.watches.push(Vec::new());
+        self.watches.push(Vec::new());
+        self.values.push(None);
+        self.levels.push(0);
+        self.reasons.push(None);
+        self.activity.push(0.0);
+        self.phases.push(false);
+        self.seen.push(false);
+        self.model.push(false);
+        self.order.push((0, var));
+        var
+    }
+
+    /// Sets the value the solver tries first for `var`.
+    pub fn set_phase(&mut self, var: u32, phase: bool) {
+        self.phases[var as usize] = phase;
+    }
+
+    pub fn add_clause(&mut self, literals: &[Lit]) {
+        if self.inconsistent {
+            return;
+        }
+        self.backtrack(0);
+
+        let mut clause = literals.to_vec();
+        clause.sort_unstable();
+        clause.dedup();
+        // a literal and its negation are neighbours after sorting
+        if clause.windows(2).any(|pair| pair[0] == !pair[1])
+            || clause.iter().any(|lit| value(&self.values, *lit) == Some(true))
+        {
+            return;
+        }
+        clause.retain(|lit| value(&self.values, *lit).is_none());
+
+        match clause.len() {
+            0 => self.inconsistent = true,
+            1 => {
+                self.assign(clause[0], None);
+                if self.propagate().is_some() {
+                    self.inconsistent = true;
+                }
+            }
+            _ => {
+                self.attach(clause);
+            }
+        }
+    }
+
+    /// Returns whether the clauses are satisfiable. The values of a
+    /// satisfying assignment are returned by [`Solver::value`].
+    pub fn solve(&mut self) -> bool {
+        if self.inconsistent {
+            return false;
+        }
+        self.backtrack(0);
+
+        let mut restarts = 0;
+        let mut conflicts = 0;
+        let mut limit = luby(restarts) * RESTART_UNIT;
+        loop {
+            if let Some(conflict) = self.propagate() {
+                if self.limits.is_empty() {
+                    self.inconsistent = true;
+                    return false;
+                }
+                conflicts += 1;
+
+                let (learnt, level) = self.analyze(conflict);
+                self.backtrack(level);
+                if learnt.len() == 1 {
+                    self.assign(learnt[0], None);
+                } else {
+                    let asserting = learnt[0];
+                    let clause = self.attach(learnt);
+                    self.assign(asserting, Some(clause));
+                }
+                self.increment /= DECAY;
+            } else if conflicts >= limit {
+                restarts += 1;
+                conflicts = 0;
+                limit = luby(restarts) * RESTART_UNIT;
+                self.backtrack(0);
+            } else {
+                match self.decide() {
+                    Some(lit) => {
+                        self.limits.push(self.trail.len());
+                        self.assign(lit, None);
+                    }
+                    None => {
+                        for (model, value) in self.model.iter_mut().zip(&self.values) {
+                            *model = value.unwrap();
+                        }
+                        return true;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the value of `var` in the last satisfying assignment.
+    pub fn value(&self, var: u32) -> bool {
+        self.model[var as usize]
+    }
+
+    fn attach(&mut self, clause: Vec<Lit>) -> usize {
+        let index = self.clauses.len();
+        self.watches[(!clause[0]).index()].push(index);
+        self.watches[(!clause[1]).index()].push(index);
+        self.clauses.push(clause);
+        index
+    }
+
+    fn assign(&mut self, lit: Lit, reason: Option<usize>) {
+        let var = lit.var() as usize;
+        self.values[var] = Some(!lit.is_negative());
+        self.levels[var] = self.limits.len();
+        self.reasons[var] = reason;
+        self.trail.push(lit);
+    }
+
+    /// Propagates the assigned literals, and returns a clause whose literals
+    /// are all false if there is one.
+    fn propagate(&mut self) -> Option<usize> {
+        while self.head < self.trail.len() {
+            let lit = self.trail[self.head];
+            self.head += 1;
+            let false_lit = !lit;
+
+            let mut watching = std::mem::take(&mut self.watches[lit.index()]);
+            let mut conflict = None;
+            let mut i = 0;
+            while i < watching.len() {
+                let index = watching[i];
+                let clause = &mut self.clauses[index];
+                if clause[0] == false_lit {
+                    clause.swap(0, 1);
+                }
+                if value(&self.values, clause[0]) == Some(true) {
+                    i += 1;
+                    continue;
+                }
+
+                if let Some(k) = (2..clause.len()).find(|k| value(&self.values, clause[*k]) != Some(false)) {
+                    clause.swap(1, k);
+                    let watch = !clause[1];
+                    self.watches[watch.index()].push(index);
+                    watching.swap_remove(i);
+                    continue;
+                }
+
+                let unit = clause[0];
+                if value(&self.values, unit) == Some(false) {
+                    conflict = Some(index);
+                    break;
+                }
+                self.assign(unit, Some(index));
+                i += 1;
+            }
+            self.watches[lit.index()] = watching;
+
+            if conflict.is_some() {
+                self.head = self.trail.len();
+                return conflict;
+            }
+        }
+        None
+    }
+
+    /// Returns the first-UIP clause learnt from `conflict`, with the
+    /// asserting literal first and a literal of the highest remaining level
+    /// second, and the level to backtrack to.
+    fn analyze(&mut self, mut conflict: usize) -> (Vec<Lit>, usize) {
+        let level = self.limits.len();
+        let mut learnt = vec![Lit(0)];
+        let mut open = 0;
+        let mut index = self.trail.len();
+        let mut implied = None;
+        loop {
+            for i in 0..self.clauses[conflict].len() {
+                let lit = self.clauses[conflict][i];
+                let var = lit.var() as usize;
+                if Some(lit) == implied || self.seen[var] || self.levels[var] == 0 {
+                    continue;
+                }
+                self.seen[var] = true;
+                self.bump(var);
+                if self.levels[var] == level {
+                    open += 1;
+                } else {
+                    learnt.push(lit);
+                }
+            }
+
+            loop {
+                index -= 1;
+                if self.seen[self.trail[index].var() as usize] {
+                    break;
+                }
+            }
+            let lit = self.trail[index];
+            self.seen[lit.var() as usize] = false;
+            implied = Some(lit);
+            open -= 1;
+            if open == 0 {
+                break;
+            }
+            conflict = self.reasons[lit.var() as usize].unwrap();
+        }
+
+        learnt[0] = !implied.unwrap();
+        for lit in &learnt[1..] {
+            self.seen[lit.var() as usize] = false;
+        }
+
+        let mut backjump = 0;
+        if learnt.len() > 1 {
+            let highest = (1..learnt.len())
+                .max_by_key(|i| self.levels[learnt[*i].var() as usize])
+                .unwrap();
+            learnt.swap(1, highest);
+            backjump = self.levels[learnt[1].var() as usize];
+        }
+        (learnt, backjump)
+    }
+
+    fn bump(&mut self, var: usize) {
+        self.activity[var] += self.increment;
+        if self.activity[var] > 1e100 {
+            for activity in &mut self.activity {
+                *activity *= 1e-100;
+            }
+            self.increment *= 1e-100;
+            self.order = (0..self.values.len())
+                .filter(|var| self.values[*var].is_none())
+                .map(|var| (self.activity[var].to_bits(), var as u32))
+                .collect();
+        }
+        self.order.push((self.activity[var].to_bits(), var as u32));
+    }
+
+    /// Returns the unassigned variable of the highest activity in its saved
+    /// phase.
+    fn decide(&mut self) -> Option<Lit> {
+        while let Some((bits, var)) = self.order.pop() {
+            let index = var as usize;
+            if self.values[index].is_none() && self.activity[index].to_bits() == bits {
+                return Some(if self.phases[index] {
+                    Lit::positive(var)
+                } else {
+                    Lit::negative(var)
+                });
+            }
+        }
+        None
+    }
+
+    fn backtrack(&mut self, level: usize) {
+        if self.limits.len() <= level {
+            return;
+        }
+        let undone = self.trail.split_off(self.limits[level]);
+        for lit in undone {
+            let var = lit.var() as usize;
+            self.phases[var] = !lit.is_negative();
+            self.values[var] = None;
+            self.reasons[var] = None;
+            self.order.push((self.activity[var].to_bits(), lit.var()));
+        }
+        self.limits.truncate(level);
+        self.head = self.trail.len();
+    }
+}
+
+/// Returns the `i`-th element of the Luby sequence 1, 1, 2, 1, 1, 2, 4, ...
+fn luby(mut i: u64) -> u64 {
+    let mut size = 1;
+    let mut exponent = 0;
+    while size < i + 1 {
+        exponent += 1;
+        size = 2 * size + 1;
+    }
+    while size - 1 != i {
+        size = (size - 1) >> 1;
+        exponent -= 1;
+        i %= size;
+    }
+    1 << exponent
+}
+
+/// Totalizer encoding of the number of true inputs, truncated at `limit`. The
+/// output for *i* is forced to be true once at least *i* inputs are true, so
+/// a unit clause with its negation bounds the number of true inputs by
+/// *i - 1*.
+pub struct Totalizer {
+    outputs: Vec<Lit>,
+}
+
+impl Totalizer {
+    pub fn new(solver: &mut Solver, inputs: &[Lit], limit: usize) -> Totalizer {
+        Totalizer {
+            outputs: Totalizer::build(solver, inputs, limit),
+        }
+    }
+
+    fn build(solver: &mut Solver, inputs: &[Lit], limit: usize) -> Vec<Lit> {
+        if inputs.len() <= 1 {
+            return inputs.to_vec();
+        }
+        let (left, right) = inputs.split_at(inputs.len() / 2);
+        let left = Totalizer::build(solver, left, limit);
+        let right = Totalizer::build(solver, right, limit);
+
+        let size = (left.len() + right.len()).min(limit);
+        let outputs: Vec<_> = (0..size).map(|_| Lit::positive(solver.new_var())).collect();
+        for i in 0..=left.len() {
+            for j in 0..=right.len() {
+                if i + j == 0 {
+                    continue;
+                }
+                let mut clause = vec![outputs[(i + j).min(size) - 1]];
+                if i > 0 {
+                    clause.push(!left[i - 1]);
+                }
+                if j > 0 {
+                    clause.push(!right[j - 1]);
+                }
+                solver.add_clause(&clause);
+            }
+        }
+        outputs
+    }
+
+    /// Returns the literal that is true if at least `count` inputs are true,
+    /// for `count` up to the limit.
+    pub fn at_least(&self, count: usize) -> Lit {
+        self.outputs[count - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Lit, Solver, Totalizer};
+
+    #[test]
+    fn solve_test_001() {
+        // three pigeons do not fit into two holes
+        let mut solver = Solver::new();
+        let holes: Vec<Vec<u32>> = (0..3).map(|_| vec![solver.new_var(), solver.new_var()]).collect();
+        for pigeon in &holes {
+            solver.add_clause(&[Lit::positive(pigeon[0]), Lit::positive(pigeon[1])]);
+        }
+        for (i, first) in holes.iter().enumerate() {
+            for second in &holes[i + 1..] {
+                for (a, b) in first.iter().zip(second) {
+                    solver.add_clause(&[Lit::negative(*a), Lit::negative(*b)]);
+                }
+            }
+        }
+        assert!(!solver.solve());
+    }
+
+    #[test]
+    fn totalizer_test_001() {
+        let mut solver = Solver::new();
+        let vars: Vec<_> = (0..6).map(|_| solver.new_var()).collect();
+        let inputs: Vec<_> = vars.iter().map(|var| Lit::positive(*var)).collect();
+        solver.add_clause(&[inputs[0], inputs[1]]);
+        solver.add_clause(&[inputs[2], inputs[3]]);
+        solver.add_clause(&[inputs[4], inputs[5]]);
+        let totalizer = Totalizer::new(&mut solver, &inputs, 4);
+
+        // at most three inputs
+        solver.add_clause(&[!totalizer.at_least(4)]);
+        assert!(solver.solve());
+        assert_eq!(vars.iter().filter(|var| solver.value(**var)).count(), 3);
+
+        // at most two inputs
+        solver.add_clause(&[!totalizer.at_least(3)]);
+        assert!(!solver.solve());
+    }
+}